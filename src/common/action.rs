@@ -1,30 +1,183 @@
 use relex::Token;
 
 use crate::common::grammar::Rhs;
+use crate::common::grammar_rules::Production;
 use crate::common::parse_tree::{ParseError, ParseTreeNode, Span};
 use crate::common::symbol_table::{NonTerminal, Terminal};
+use crate::common::tree_rules::TreeRules;
 
 pub trait Action {
     type ParseResult;
     type ParseError;
 
-    fn on_reduce(&mut self, non_terminal: &NonTerminal, rhs: &Rhs<Terminal, NonTerminal, ()>);
-    fn on_shift(&mut self, token: Token<Terminal>);
+    /// Called when a rule is reduced, with the span of its children, or the empty span before the
+    /// lookahead for an empty rule.
+    fn on_reduce(
+        &mut self,
+        non_terminal: &NonTerminal,
+        rhs: &Rhs<Terminal, NonTerminal, Production>,
+        span: Span,
+    );
+    /// Called when a token is shifted, with its span.
+    fn on_shift(&mut self, token: Token<Terminal>, span: Span);
     fn on_accept(&mut self) -> Self::ParseResult;
-    fn on_error(&mut self, token: Token<Terminal>, span: Span) -> Self::ParseError;
+    /// Called on a syntax error, with the terminals the parser expected instead of the token.
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError;
+
+    /// Called when error recovery pops a state, discarding the value of its symbol.
+    fn on_pop(&mut self) {}
+
+    /// Called by `Parser::reset`, to clear the state the action keeps between inputs.
+    fn on_reset(&mut self) {}
+}
+
+/// An action whose parser owns the stack of semantic values, one for each symbol on the parse
+/// stack. Run it by the `Action` of a `ValueStack`.
+pub trait ValueAction {
+    type Value;
+    type ParseResult;
+    type ParseError;
+
+    /// Called when a rule is reduced, with the values of its children in order, returning the
+    /// value of the non terminal. The children are dropped afterwards, so they may be moved out.
+    fn on_reduce(
+        &mut self,
+        non_terminal: &NonTerminal,
+        rhs: &Rhs<Terminal, NonTerminal, Production>,
+        children: &mut [Self::Value],
+        span: Span,
+    ) -> Self::Value;
+    /// Called when a token is shifted, returning its value. The `error` terminal is shifted by
+    /// error recovery.
+    fn on_shift(&mut self, token: Token<Terminal>, span: Span) -> Self::Value;
+    /// Called with the value of the start symbol when the input is accepted.
+    fn on_accept(&mut self, value: Self::Value) -> Self::ParseResult;
+    /// Called on a syntax error, with the terminals the parser expected instead of the token.
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError;
+
+    /// Called by `Parser::reset`, to clear the state the action keeps between inputs.
+    fn on_reset(&mut self) {}
+}
+
+/// The `Action` of a `ValueAction`, keeping its values on a stack in step with the parse stack.
+pub struct ValueStack<A: ValueAction> {
+    action: A,
+    values: Vec<A::Value>,
+}
+
+impl<A: ValueAction> ValueStack<A> {
+    /// Creates an empty value stack for the action.
+    #[must_use]
+    pub const fn new(action: A) -> Self {
+        Self {
+            action,
+            values: Vec::new(),
+        }
+    }
+
+    /// Returns the action, dropping the values of an unfinished parse.
+    #[must_use]
+    pub fn into_inner(self) -> A {
+        self.action
+    }
+}
+
+impl<A: ValueAction> Action for ValueStack<A> {
+    type ParseResult = A::ParseResult;
+    type ParseError = A::ParseError;
+
+    fn on_reduce(
+        &mut self,
+        non_terminal: &NonTerminal,
+        rhs: &Rhs<Terminal, NonTerminal, Production>,
+        span: Span,
+    ) {
+        let start = self.values.len().saturating_sub(rhs.syms.len());
+        let value = self
+            .action
+            .on_reduce(non_terminal, rhs, &mut self.values[start..], span);
+        self.values.truncate(start);
+        self.values.push(value);
+    }
+
+    fn on_shift(&mut self, token: Token<Terminal>, span: Span) {
+        let value = self.action.on_shift(token, span);
+        self.values.push(value);
+    }
+
+    /// # Panics
+    ///
+    /// This function will panic if the value stack is empty, which the parsers never accept.
+    fn on_accept(&mut self) -> Self::ParseResult {
+        let value = self
+            .values
+            .pop()
+            .expect("Accepted without the value of the start symbol");
+        self.values.clear();
+        self.action.on_accept(value)
+    }
+
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        self.action.on_error(token, span, expected)
+    }
+
+    fn on_pop(&mut self) {
+        self.values.pop();
+    }
+
+    fn on_reset(&mut self) {
+        self.values.clear();
+        self.action.on_reset();
+    }
 }
 
 pub struct DefaultAction {
     node_stack: Vec<ParseTreeNode>,
     start_symbol: NonTerminal,
+    tree_rules: Option<TreeRules>,
 }
 
 impl DefaultAction {
+    /// Creates a `DefaultAction` building the full derivation tree.
     #[must_use]
     pub const fn new(start_symbol: NonTerminal) -> Self {
         Self {
             node_stack: Vec::new(),
             start_symbol,
+            tree_rules: None,
+        }
+    }
+
+    /// Creates a `DefaultAction` building a tree shaped by the `@hide` and `@drop` rules.
+    #[must_use]
+    pub const fn with_tree_rules(start_symbol: NonTerminal, tree_rules: TreeRules) -> Self {
+        Self {
+            node_stack: Vec::new(),
+            start_symbol,
+            tree_rules: Some(tree_rules),
+        }
+    }
+
+    /// Shape the children of a new node by the tree rules, if any.
+    fn shape(&self, children: Vec<ParseTreeNode>) -> Vec<ParseTreeNode> {
+        match &self.tree_rules {
+            Some(tree_rules) => tree_rules.shape(children),
+            None => children,
         }
     }
 }
@@ -36,7 +189,8 @@ impl Action for DefaultAction {
     fn on_reduce(
         &mut self,
         non_terminal: &NonTerminal,
-        rhs: &crate::common::grammar::Rhs<Terminal, NonTerminal, ()>,
+        rhs: &Rhs<Terminal, NonTerminal, Production>,
+        span: Span,
     ) {
         // Build nonterminal node.
         let length = rhs.syms.len();
@@ -48,26 +202,43 @@ impl Action for DefaultAction {
             }
         }
         children.reverse();
-        let new_node =
-            ParseTreeNode::non_terminal(non_terminal.clone(), children, Span::new(0, 0, 1, 1));
+        let children = self.shape(children);
+        let new_node = ParseTreeNode::non_terminal(non_terminal.clone(), children, span);
         self.node_stack.push(new_node);
     }
 
-    fn on_shift(&mut self, token: Token<Terminal>) {
-        let new_node =
-            ParseTreeNode::terminal(token.kind, token.text.to_string(), Span::new(0, 0, 1, 1));
+    fn on_shift(&mut self, token: Token<Terminal>, span: Span) {
+        let new_node = ParseTreeNode::terminal(token.kind, token.text.to_string(), span);
         self.node_stack.push(new_node);
     }
 
     fn on_accept(&mut self) -> Self::ParseResult {
-        let children = std::mem::take(&mut self.node_stack);
-        ParseTreeNode::non_terminal(self.start_symbol.clone(), children, Span::new(0, 0, 1, 1))
+        // With an augmented grammar, the input has been reduced to a single start node.
+        let mut children = std::mem::take(&mut self.node_stack);
+        if let [node] = children.as_slice()
+            && node.is_non_terminal(&self.start_symbol)
+        {
+            return children.remove(0);
+        }
+        let span = ParseTreeNode::span_of(&children);
+        let children = self.shape(children);
+        ParseTreeNode::non_terminal(self.start_symbol.clone(), children, span)
     }
 
-    fn on_error(&mut self, token: Token<Terminal>, span: Span) -> Self::ParseError {
-        ParseError {
-            message: format!("Unexpected token: {:?}", token.kind),
-            span: Some(span),
-        }
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        ParseError::unexpected(&token.kind, expected, span)
+    }
+
+    fn on_pop(&mut self) {
+        self.node_stack.pop();
+    }
+
+    fn on_reset(&mut self) {
+        self.node_stack.clear();
     }
 }
@@ -0,0 +1,459 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::{self, Display};
+
+use crate::common::grammar::{ConflictReport, Item, LR1Conflict, NonTerminal, Symbol, Terminal};
+
+/// A derivation of the input around a conflict, from an outer rule down to a conflicting item.
+#[derive(Debug)]
+pub struct Derivation<'a, T: 'a, N: 'a, A: 'a> {
+    /// Symbols leading from the initial state to the conflict state.
+    pub prefix: Vec<&'a Symbol<T, N>>,
+    /// Items from the outermost rule down to the conflicting item, where the dot of each item
+    /// is before the nonterminal derived by the next one.
+    pub items: Vec<Item<'a, T, N, A>>,
+}
+
+/// A counterexample of a conflict, deriving the same prefix for both conflicting actions.
+#[derive(Debug)]
+pub struct Counterexample<'a, T: 'a, N: 'a, A: 'a> {
+    /// Derivation reducing by the (first) rule of the conflict.
+    pub first: Derivation<'a, T, N, A>,
+    /// Derivation shifting the token, or reducing by the second rule of the conflict.
+    pub second: Derivation<'a, T, N, A>,
+}
+
+/// Whether a lookahead can follow the end of a nonterminal within a rule.
+enum Follows {
+    /// The lookahead is the first terminal of the rest of the rule.
+    Directly,
+    /// The rest of the rule is nullable, so the lookahead has to follow the rule itself.
+    Nullable,
+    /// The lookahead cannot follow.
+    Never,
+}
+
+/// The goal when climbing from an item to the outer rules along a fixed prefix.
+enum Goal<'a, T> {
+    /// Reach a rule where the lookahead follows.
+    Lookahead(Option<&'a T>),
+    /// Reach a rule starting at or before the given position of the prefix.
+    Position(usize),
+}
+
+/// A node of the backward search, a nonterminal predicted at a state or prefix position.
+struct Node<'a, T: 'a, N: 'a, A: 'a> {
+    at: usize,
+    non_terminal: &'a N,
+    /// The previous node and the item in it deriving this nonterminal.
+    via: Option<(usize, Item<'a, T, N, A>)>,
+}
+
+/// The tables of the counterexample search, built once per conflict report.
+#[derive(Debug)]
+pub(crate) struct SearchTables<'a, T: 'a, N: 'a> {
+    first_sets: BTreeMap<&'a N, (BTreeSet<&'a T>, bool)>,
+    /// The states with a transition into each state.
+    predecessors: Vec<BTreeSet<usize>>,
+    /// The previous state and symbol on a shortest path from the initial state to each state.
+    parents: Vec<Option<(usize, &'a Symbol<T, N>)>>,
+}
+
+/// Searches counterexamples in the LR(0) state machine of a conflict report.
+struct Search<'r, 'a, T: 'a, N: 'a, A: 'a> {
+    report: &'r ConflictReport<'a, T, N, A>,
+    tables: &'r SearchTables<'a, T, N>,
+}
+
+impl<'a, T: Ord, N: Ord> SearchTables<'a, T, N> {
+    fn new<A>(report: &ConflictReport<'a, T, N, A>) -> Self {
+        let states = &report.state_machine.states;
+        let mut predecessors = vec![BTreeSet::new(); states.len()];
+        let mut parents = vec![None; states.len()];
+        let mut visited = BTreeSet::from([0]);
+        let mut queue = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for (&symbol, &target) in &states[state].1 {
+                predecessors[target].insert(state);
+                if visited.insert(target) {
+                    parents[target] = Some((state, symbol));
+                    queue.push_back(target);
+                }
+            }
+        }
+        Self {
+            first_sets: report.grammar.first_sets(),
+            predecessors,
+            parents,
+        }
+    }
+}
+
+impl<'r, 'a, T: Ord, N: Ord, A> Search<'r, 'a, T, N, A> {
+    fn new(report: &'r ConflictReport<'a, T, N, A>) -> Self {
+        let tables = report
+            .search_tables
+            .get_or_init(|| Box::new(SearchTables::new(report)));
+        Self { report, tables }
+    }
+
+    /// The symbols of a shortest path from the initial state to the state.
+    fn path_to(&self, mut state: usize) -> Vec<&'a Symbol<T, N>> {
+        let mut path = Vec::new();
+        while let Some((parent, symbol)) = self.tables.parents[state] {
+            path.push(symbol);
+            state = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// The states from which `steps` transitions lead to the state.
+    fn back(&self, state: usize, steps: usize) -> BTreeSet<usize> {
+        let mut states = BTreeSet::from([state]);
+        for _ in 0..steps {
+            states = states
+                .iter()
+                .flat_map(|&state| self.tables.predecessors[state].iter().copied())
+                .collect();
+        }
+        states
+    }
+
+    /// Whether the lookahead can follow a nonterminal followed by `rest` in a rule.
+    fn follows(&self, rest: &[Symbol<T, N>], lookahead: Option<&T>) -> Follows {
+        for symbol in rest {
+            match symbol {
+                Terminal(t) => {
+                    return if lookahead == Some(t) {
+                        Follows::Directly
+                    } else {
+                        Follows::Never
+                    };
+                }
+                NonTerminal(n) => {
+                    let (first, nullable) = &self.tables.first_sets[n];
+                    if lookahead.is_some_and(|lookahead| first.contains(lookahead)) {
+                        return Follows::Directly;
+                    }
+                    if !nullable {
+                        return Follows::Never;
+                    }
+                }
+            }
+        }
+        Follows::Nullable
+    }
+
+    /// The items of a state whose dot is before the nonterminal.
+    fn predicting(
+        &self,
+        state: usize,
+        non_terminal: &N,
+    ) -> impl Iterator<Item = &Item<'a, T, N, A>> {
+        self.report.state_machine.states[state]
+            .0
+            .items
+            .iter()
+            .filter(move |item| {
+                matches!(item.rhs.syms.get(item.pos), Some(NonTerminal(n)) if n == non_terminal)
+            })
+    }
+
+    /// Collect the items from the outer item, if any, through the found nodes down to `bottom`.
+    fn chain(
+        nodes: &[Node<'a, T, N, A>],
+        mut index: usize,
+        outer: Option<&Item<'a, T, N, A>>,
+        bottom: &Item<'a, T, N, A>,
+    ) -> Vec<Item<'a, T, N, A>> {
+        let mut items: Vec<_> = outer.into_iter().cloned().collect();
+        while let Some((previous, ref item)) = nodes[index].via {
+            items.push(item.clone());
+            index = previous;
+        }
+        items.push(bottom.clone());
+        items
+    }
+
+    /// Search the whole state machine for a derivation reducing `item` in `state` on the
+    /// lookahead, climbing through the rules in which the lookahead can follow the reduction.
+    fn reduce(
+        &self,
+        state: usize,
+        item: &Item<'a, T, N, A>,
+        lookahead: Option<&'a T>,
+    ) -> Option<Derivation<'a, T, N, A>> {
+        let mut nodes = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        for origin in self.back(state, item.pos) {
+            visited.insert((origin, item.lhs));
+            nodes.push(Node {
+                at: origin,
+                non_terminal: item.lhs,
+                via: None,
+            });
+            queue.push_back(nodes.len() - 1);
+        }
+
+        while let Some(index) = queue.pop_front() {
+            let (at, non_terminal) = (nodes[index].at, nodes[index].non_terminal);
+
+            // The start symbol is followed by EOF.
+            if lookahead.is_none() && at == 0 && *non_terminal == self.report.grammar.start {
+                let items = Self::chain(&nodes, index, None, item);
+                let prefix = self.prefix(self.path_to(0), &items);
+                return Some(Derivation { prefix, items });
+            }
+
+            for outer in self.predicting(at, non_terminal) {
+                match self.follows(&outer.rhs.syms[outer.pos + 1..], lookahead) {
+                    Follows::Directly => {
+                        // Start from the closest state in which the outer item is predicted.
+                        let origin = self
+                            .back(at, outer.pos)
+                            .into_iter()
+                            .min_by_key(|&origin| self.path_to(origin).len())?;
+                        let items = Self::chain(&nodes, index, Some(outer), item);
+                        let prefix = self.prefix(self.path_to(origin), &items);
+                        return Some(Derivation { prefix, items });
+                    }
+                    Follows::Nullable => {
+                        for origin in self.back(at, outer.pos) {
+                            if visited.insert((origin, outer.lhs)) {
+                                nodes.push(Node {
+                                    at: origin,
+                                    non_terminal: outer.lhs,
+                                    via: Some((index, outer.clone())),
+                                });
+                                queue.push_back(nodes.len() - 1);
+                            }
+                        }
+                    }
+                    Follows::Never => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// Extend the path to the state predicting the outermost item by the symbols before the
+    /// dots of the items.
+    fn prefix(
+        &self,
+        mut path: Vec<&'a Symbol<T, N>>,
+        items: &[Item<'a, T, N, A>],
+    ) -> Vec<&'a Symbol<T, N>> {
+        path.extend(items.iter().flat_map(|item| &item.rhs.syms[..item.pos]));
+        path
+    }
+
+    /// Climb from `item` at the end of a fixed prefix to the outer rules that reach the goal.
+    fn climb(
+        &self,
+        prefix: &[&'a Symbol<T, N>],
+        item: &Item<'a, T, N, A>,
+        goal: &Goal<'a, T>,
+    ) -> Option<Derivation<'a, T, N, A>> {
+        // The states along the prefix.
+        let transitions = &self.report.state_machine.states;
+        let mut states = vec![0];
+        for symbol in prefix {
+            let state = transitions[*states.last()?].1.get(symbol)?;
+            states.push(*state);
+        }
+
+        let derivation = |nodes: &[Node<'a, T, N, A>], index, outer| Derivation {
+            prefix: prefix.to_vec(),
+            items: Self::chain(nodes, index, outer, item),
+        };
+        let mut nodes = vec![Node {
+            at: prefix.len().checked_sub(item.pos)?,
+            non_terminal: item.lhs,
+            via: None,
+        }];
+        let mut visited = BTreeSet::from([(nodes[0].at, item.lhs)]);
+        let mut queue = VecDeque::from([0]);
+        let mut closest = 0;
+        while let Some(index) = queue.pop_front() {
+            let (at, non_terminal) = (nodes[index].at, nodes[index].non_terminal);
+            match *goal {
+                Goal::Position(position) if at <= position => {
+                    return Some(derivation(&nodes, index, None));
+                }
+                Goal::Lookahead(None) if at == 0 && *non_terminal == self.report.grammar.start => {
+                    return Some(derivation(&nodes, index, None));
+                }
+                _ => {}
+            }
+            if at < nodes[closest].at {
+                closest = index;
+            }
+
+            for outer in self.predicting(states[at], non_terminal) {
+                let climb = match *goal {
+                    Goal::Lookahead(lookahead) => {
+                        match self.follows(&outer.rhs.syms[outer.pos + 1..], lookahead) {
+                            Follows::Directly => {
+                                return Some(derivation(&nodes, index, Some(outer)));
+                            }
+                            Follows::Nullable => true,
+                            Follows::Never => false,
+                        }
+                    }
+                    Goal::Position(_) => true,
+                };
+                if climb && outer.pos <= at && visited.insert((at - outer.pos, outer.lhs)) {
+                    nodes.push(Node {
+                        at: at - outer.pos,
+                        non_terminal: outer.lhs,
+                        via: Some((index, outer.clone())),
+                    });
+                    queue.push_back(nodes.len() - 1);
+                }
+            }
+        }
+
+        // Without reaching the position, derive as far out as possible.
+        match goal {
+            Goal::Position(_) => Some(derivation(&nodes, closest, None)),
+            Goal::Lookahead(_) => None,
+        }
+    }
+}
+
+impl<'a, T: Ord, N: Ord, A> ConflictReport<'a, T, N, A> {
+    /// Compute a counterexample of a conflict of this report.
+    ///
+    /// The first derivation reduces by the (first) rule of the conflict in a context where the
+    /// token follows. The second derivation shifts the token, or reduces by the second rule,
+    /// after the same prefix where possible, so that both show how the prefix can be parsed.
+    /// Returns `None` if no derivation leads to the conflict.
+    #[must_use]
+    pub fn counterexample(
+        &self,
+        conflict: &LR1Conflict<'a, T, N, A>,
+    ) -> Option<Counterexample<'a, T, N, A>> {
+        let search = Search::new(self);
+        let items = conflict.items();
+        let complete = |(lhs, rhs): (&'a N, &'a _)| {
+            items
+                .iter()
+                .find(|item| item.lhs == lhs && std::ptr::eq(item.rhs, rhs))
+                .copied()
+        };
+        match *conflict {
+            LR1Conflict::ShiftReduce {
+                state_id,
+                token,
+                rule,
+                ..
+            } => {
+                let first = search.reduce(state_id, complete(rule)?, token)?;
+                let shift = items.iter().find(|item| item.pos < item.rhs.syms.len())?;
+                let second = search.climb(&first.prefix, shift, &Goal::Position(first.start()))?;
+                Some(Counterexample { first, second })
+            }
+            LR1Conflict::ReduceReduce {
+                state_id,
+                token,
+                r1,
+                r2,
+                ..
+            } => {
+                let first = search.reduce(state_id, complete(r1)?, token)?;
+                let r2 = complete(r2)?;
+                let second = search
+                    .climb(&first.prefix, r2, &Goal::Lookahead(token))
+                    .or_else(|| search.reduce(state_id, r2, token))?;
+                Some(Counterexample { first, second })
+            }
+        }
+    }
+}
+
+impl<T, N, A> Derivation<'_, T, N, A> {
+    /// Whether the derivation shifts, rather than reduces, at the conflict.
+    #[must_use]
+    pub fn is_shift(&self) -> bool {
+        self.items
+            .last()
+            .is_some_and(|item| item.pos < item.rhs.syms.len())
+    }
+
+    /// The position in the prefix at which the outermost item starts.
+    #[must_use]
+    pub fn start(&self) -> usize {
+        self.prefix.len() - self.items.iter().map(|item| item.pos).sum::<usize>()
+    }
+}
+
+impl<T: Display, N: Display, A> Derivation<'_, T, N, A> {
+    /// The sentential form of the derivation, with `•` marking the conflict position.
+    #[must_use]
+    pub fn example(&self) -> String {
+        let mut symbols: Vec<String> = self.prefix.iter().map(ToString::to_string).collect();
+        symbols.push("•".to_string());
+        if let Some((last, outer)) = self.items.split_last() {
+            symbols.extend(last.rhs.syms[last.pos..].iter().map(ToString::to_string));
+            for item in outer.iter().rev() {
+                symbols.extend(
+                    item.rhs.syms[item.pos + 1..]
+                        .iter()
+                        .map(ToString::to_string),
+                );
+            }
+        }
+        symbols.join(" ")
+    }
+}
+
+impl<T: Display, N: Display, A> Display for Derivation<'_, T, N, A> {
+    /// Format the derivation with each rule in brackets, e.g. `[a -> x [b -> y •] z]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for symbol in &self.prefix[..self.start()] {
+            write!(f, "{symbol} ")?;
+        }
+        for (index, item) in self.items.iter().enumerate() {
+            write!(f, "[{} ->", item.lhs)?;
+            for symbol in &item.rhs.syms[..item.pos] {
+                write!(f, " {symbol}")?;
+            }
+            if index + 1 == self.items.len() {
+                write!(f, " •")?;
+                for symbol in &item.rhs.syms[item.pos..] {
+                    write!(f, " {symbol}")?;
+                }
+            } else {
+                write!(f, " ")?;
+            }
+        }
+        for index in (0..self.items.len()).rev() {
+            write!(f, "]")?;
+            if let Some(outer) = index.checked_sub(1).map(|outer| &self.items[outer]) {
+                for symbol in &outer.rhs.syms[outer.pos + 1..] {
+                    write!(f, " {symbol}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Display, N: Display, A> Display for Counterexample<'_, T, N, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, derivation) in [&self.first, &self.second].into_iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            let action = if derivation.is_shift() {
+                "Shift"
+            } else {
+                "Reduce"
+            };
+            writeln!(f, "{action} example: {}", derivation.example())?;
+            write!(f, "{action} derivation: {derivation}")?;
+        }
+        Ok(())
+    }
+}
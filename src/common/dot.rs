@@ -0,0 +1,99 @@
+use std::fmt::{Display, Write};
+
+use crate::common::parse_tree::ParseTreeNode;
+use crate::common::report::Automaton;
+
+impl<T: Ord + Display, N: Ord + Display, A> Automaton<'_, '_, T, N, A> {
+    /// Export the state machine as a Graphviz DOT graph, with the kernel items of every
+    /// state and its transitions labeled by symbol. States with conflicts are drawn in red.
+    #[must_use]
+    pub fn dot(&self) -> String {
+        let mut dot = String::from("digraph automaton {\n");
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+
+        for (index, (items, _)) in self.state_machine.states.iter().enumerate() {
+            let mut label = format!("State {index}\\l");
+            for item in items
+                .items
+                .iter()
+                .filter(|item| item.pos > 0 || *item.lhs == self.grammar.start)
+            {
+                label.push_str(&escape(&item.to_string()));
+                label.push_str("\\l");
+            }
+            let conflict = self
+                .conflicts
+                .iter()
+                .any(|conflict| conflict.state_id() == index);
+            let style = if conflict {
+                ", color=red, fontcolor=red, penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(dot, "  state{index} [label=\"{label}\"{style}];").unwrap();
+        }
+
+        for (index, (_, transitions)) in self.state_machine.states.iter().enumerate() {
+            for (symbol, target) in transitions {
+                let label = escape(&symbol.to_string());
+                writeln!(dot, "  state{index} -> state{target} [label=\"{label}\"];").unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl ParseTreeNode {
+    /// Export the parse tree as a Graphviz DOT graph. Non terminals are drawn as ellipses,
+    /// terminals as boxes with their token and lexeme.
+    #[must_use]
+    pub fn dot(&self) -> String {
+        let mut dot = String::from("digraph parse_tree {\n");
+        dot.push_str("  node [fontname=\"monospace\"];\n");
+        let mut count = 0;
+        write_node(&mut dot, self, &mut count);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Write a parse tree node and its children, numbering the nodes in pre-order. Returns the
+/// number of the written node.
+fn write_node(dot: &mut String, node: &ParseTreeNode, count: &mut usize) -> usize {
+    let id = *count;
+    *count += 1;
+    match node {
+        ParseTreeNode::Terminal { token, lexeme, .. } => {
+            let label = escape(&format!("{token}\n{lexeme}"));
+            writeln!(dot, "  node{id} [label=\"{label}\", shape=box];").unwrap();
+        }
+        ParseTreeNode::NonTerminal {
+            symbol, children, ..
+        } => {
+            let label = escape(&symbol.to_string());
+            writeln!(dot, "  node{id} [label=\"{label}\"];").unwrap();
+            for child in children {
+                let child = write_node(dot, child, count);
+                writeln!(dot, "  node{id} -> node{child};").unwrap();
+            }
+        }
+    }
+    id
+}
+
+/// Escape a string for a quoted DOT label.
+fn escape(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for char in str.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
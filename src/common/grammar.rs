@@ -2,6 +2,9 @@ use std::cell::RefCell;
 use std::cmp;
 use std::collections::{BTreeMap, BTreeSet, VecDeque, btree_map};
 use std::fmt::{self, Debug, Display};
+use std::sync::OnceLock;
+
+use crate::common::counterexample::SearchTables;
 
 /// Type alias for LR(0) state machine states.
 type LR0States<'a, T, N, A> = Vec<(ItemSet<'a, T, N, A>, BTreeMap<&'a Symbol<T, N>, usize>)>;
@@ -9,6 +12,19 @@ type LR0States<'a, T, N, A> = Vec<(ItemSet<'a, T, N, A>, BTreeMap<&'a Symbol<T,
 /// Type alias for the result of advancing an item.
 type AdvanceResult<'a, T, N, A> = Option<(&'a Symbol<T, N>, Item<'a, T, N, A>)>;
 
+/// Type alias for a lookahead set, with whether it contains EOF.
+type Lookahead<'a, T> = (BTreeSet<&'a T>, bool);
+
+/// Type alias for LR(1) items, mapping each item to its lookahead set.
+type LR1Items<'a, T, N, A> = BTreeMap<Item<'a, T, N, A>, Lookahead<'a, T>>;
+
+/// Type alias for LR(1) states, each of which consists of its items and its transitions.
+type LR1States<'a, T, N, A> = Vec<(LR1Items<'a, T, N, A>, BTreeMap<&'a Symbol<T, N>, usize>)>;
+
+/// Type alias for the reductions of a state machine, as the end state, the rule and the
+/// lookahead set of each reduction.
+type Reductions<'a, T, N, A> = Vec<(usize, &'a N, &'a Rhs<T, N, A>, Lookahead<'a, T>)>;
+
 pub use Symbol::*;
 
 /// A symbol in a context-free grammar.
@@ -86,6 +102,38 @@ pub struct Item<'a, T: 'a, N: 'a, A: 'a> {
 }
 comparators!(Item('a, T, N, A) (T, N) (lhs, rhs, pos));
 
+impl<T: Display, N: Display, A> Display for Rhs<T, N, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        if self.syms.is_empty() {
+            return write!(f, "EPSILON");
+        }
+        for (i, sym) in self.syms.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{sym}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Display, N: Display, A> Display for Item<'_, T, N, A> {
+    /// Format the item in grammar notation, e.g. `expr -> expr . "+" term`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{} ->", self.lhs)?;
+        for (i, sym) in self.rhs.syms.iter().enumerate() {
+            if i == self.pos {
+                write!(f, " .")?;
+            }
+            write!(f, " {sym}")?;
+        }
+        if self.pos == self.rhs.syms.len() {
+            write!(f, " .")?;
+        }
+        Ok(())
+    }
+}
+
 impl<T, N, A> Clone for Item<'_, T, N, A> {
     fn clone(&self) -> Self {
         Item {
@@ -140,6 +188,26 @@ pub enum LRAction<'a, T: 'a, N: 'a, A: 'a> {
     Accept,
 }
 
+impl<T, N, A> Clone for LRAction<'_, T, N, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, N, A> Copy for LRAction<'_, T, N, A> {}
+
+impl<T: PartialEq, N: PartialEq, A> PartialEq for LRAction<'_, T, N, A> {
+    /// Compare actions, where reductions are equal if they reduce by the same rule.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Reduce(l1, r1), Self::Reduce(l2, r2)) => l1 == l2 && std::ptr::eq(*r1, *r2),
+            (Self::Shift(s1), Self::Shift(s2)) => s1 == s2,
+            (Self::Accept, Self::Accept) => true,
+            _ => false,
+        }
+    }
+}
+
 /// A state in an LR(1) parse table.
 #[derive(Debug)]
 pub struct LR1State<'a, T: 'a, N: 'a, A: 'a> {
@@ -157,39 +225,239 @@ pub struct LR1ParseTable<'a, T: 'a, N: 'a, A: 'a> {
     pub states: Vec<LR1State<'a, T, N, A>>,
 }
 
+/// A state in a GLR parse table, which keeps every action of a conflict.
+#[derive(Debug)]
+pub struct GLRState<'a, T: 'a, N: 'a, A: 'a> {
+    /// The actions if the lookahead is EOF.
+    pub eof: Vec<LRAction<'a, T, N, A>>,
+    /// The actions for each non-EOF lookahead.
+    pub lookahead: BTreeMap<&'a T, Vec<LRAction<'a, T, N, A>>>,
+    /// The state to jump to when shifting a nonterminal (because of a reduce rule).
+    pub goto: BTreeMap<&'a N, usize>,
+}
+
+/// A GLR parse table, an LR(1) parse table with several actions for conflicting cells.
+#[derive(Debug)]
+pub struct GLRParseTable<'a, T: 'a, N: 'a, A: 'a> {
+    pub states: Vec<GLRState<'a, T, N, A>>,
+}
+
+/// The construction of the states of an LR(1) parse table, from the weakest to the strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum LR1Construction {
+    /// LR(0): the LR(0) states, reducing complete items on every lookahead.
+    Lr0,
+    /// SLR(1): the LR(0) states, reducing complete items on the FOLLOW set of their rule.
+    Slr,
+    /// LALR(1): the LR(0) states with lookaheads, which may have spurious reduce-reduce
+    /// conflicts for grammars that are LR(1).
+    #[default]
+    Lalr,
+    /// Canonical LR(1): a state for every distinct set of LR(1) items, which can be many.
+    Canonical,
+    /// Minimal LR(1): the canonical LR(1) states, merging states with the same LR(0) items
+    /// wherever that does not introduce a conflict. This is as small as LALR(1) for LALR(1)
+    /// grammars, without its spurious conflicts.
+    Minimal,
+}
+
+impl Display for LR1Construction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Lr0 => write!(f, "LR(0)"),
+            Self::Slr => write!(f, "SLR(1)"),
+            Self::Lalr => write!(f, "LALR(1)"),
+            Self::Canonical => write!(f, "LR(1)"),
+            Self::Minimal => write!(f, "minimal LR(1)"),
+        }
+    }
+}
+
+/// The resolution of a shift-reduce conflict, e.g. by precedence and associativity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the shift.
+    Shift,
+    /// Replace the shift by the reduction.
+    Reduce,
+    /// Neither shift nor reduce, making the token a syntax error.
+    Error,
+}
+
 /// A conflict detected while trying to construct an LR(1) parse table.
 #[derive(Debug)]
 pub enum LR1Conflict<'a, T: 'a, N: 'a, A: 'a> {
     /// A reduce-reduce conflict.
     ReduceReduce {
+        /// The index of the state in the parse table.
+        state_id: usize,
         /// The LR(0) state in which the conflict occurs.
-        #[allow(dead_code)]
         state: ItemSet<'a, T, N, A>,
         /// The token leading to the conflict, or `None` if the token is EOF.
-        #[allow(dead_code)]
         token: Option<&'a T>,
         /// The first conflicting rule.
-        #[allow(dead_code)]
         r1: (&'a N, &'a Rhs<T, N, A>),
         /// The second conflicting rule.
-        #[allow(dead_code)]
         r2: (&'a N, &'a Rhs<T, N, A>),
     },
     /// A shift-reduce conflict.
     ShiftReduce {
+        /// The index of the state in the parse table.
+        state_id: usize,
         /// The LR(0) state in which the conflict appears.
-        #[allow(dead_code)]
         state: ItemSet<'a, T, N, A>,
         /// The token leading to the conflict, or `None` if the token is EOF.
-        #[allow(dead_code)]
         token: Option<&'a T>,
         /// The reduce rule involved in the conflict.
-        #[allow(dead_code)]
         rule: (&'a N, &'a Rhs<T, N, A>),
     },
 }
 
+impl<'a, T: PartialEq, N: PartialEq, A> LR1Conflict<'a, T, N, A> {
+    /// The index of the state in the parse table.
+    #[must_use]
+    pub const fn state_id(&self) -> usize {
+        match *self {
+            Self::ReduceReduce { state_id, .. } | Self::ShiftReduce { state_id, .. } => state_id,
+        }
+    }
+
+    /// The token leading to the conflict, or `None` if the token is EOF.
+    #[must_use]
+    pub const fn token(&self) -> Option<&'a T> {
+        match *self {
+            Self::ReduceReduce { token, .. } | Self::ShiftReduce { token, .. } => token,
+        }
+    }
+
+    /// Whether both conflicts are in the same cell of the parse table between the same rules.
+    fn is_duplicate(&self, other: &Self) -> bool {
+        let same = |(l1, r1): (&N, &Rhs<T, N, A>), (l2, r2): (&N, &Rhs<T, N, A>)| {
+            l1 == l2 && std::ptr::eq(r1, r2)
+        };
+        match (self, other) {
+            (
+                Self::ReduceReduce {
+                    state_id,
+                    token,
+                    r1,
+                    r2,
+                    ..
+                },
+                Self::ReduceReduce {
+                    state_id: other_state_id,
+                    token: other_token,
+                    r1: other_r1,
+                    r2: other_r2,
+                    ..
+                },
+            ) => {
+                state_id == other_state_id
+                    && token == other_token
+                    && (same(*r1, *other_r1) && same(*r2, *other_r2)
+                        || same(*r1, *other_r2) && same(*r2, *other_r1))
+            }
+            (
+                Self::ShiftReduce {
+                    state_id,
+                    token,
+                    rule,
+                    ..
+                },
+                Self::ShiftReduce {
+                    state_id: other_state_id,
+                    token: other_token,
+                    rule: other_rule,
+                    ..
+                },
+            ) => state_id == other_state_id && token == other_token && same(*rule, *other_rule),
+            _ => false,
+        }
+    }
+
+    /// The items of the state involved in the conflict, which are the completed items of the
+    /// reduce rules and, for shift-reduce conflicts, the items shifting the token.
+    #[must_use]
+    pub fn items(&self) -> Vec<&Item<'a, T, N, A>> {
+        let is_complete = |item: &Item<'a, T, N, A>, (lhs, rhs): (&N, &Rhs<T, N, A>)| {
+            item.lhs == lhs && std::ptr::eq(item.rhs, rhs) && item.pos == rhs.syms.len()
+        };
+        match self {
+            Self::ReduceReduce { state, r1, r2, .. } => state
+                .items
+                .iter()
+                .filter(|item| is_complete(item, *r1) || is_complete(item, *r2))
+                .collect(),
+            Self::ShiftReduce {
+                state, token, rule, ..
+            } => state
+                .items
+                .iter()
+                .filter(|item| {
+                    is_complete(item, *rule)
+                        || matches!(
+                            (item.rhs.syms.get(item.pos), token),
+                            (Some(Terminal(t)), Some(token)) if t == *token
+                        )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T: PartialEq + Display, N: PartialEq + Display, A> Display for LR1Conflict<'_, T, N, A> {
+    /// Format the conflict followed by its involved items, one per line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let kind = match self {
+            Self::ReduceReduce { .. } => "reduce/reduce",
+            Self::ShiftReduce { .. } => "shift/reduce",
+        };
+        write!(f, "State {}: {kind} conflict on ", self.state_id())?;
+        match self.token() {
+            Some(token) => write!(f, "{token}")?,
+            None => write!(f, "<EOF>")?,
+        }
+        for item in self.items() {
+            write!(f, "\n  {item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// All conflicts detected while trying to construct an LR(1) parse table.
+#[derive(Debug)]
+pub struct ConflictReport<'a, T: 'a, N: 'a, A: 'a> {
+    /// The conflicts, ordered by state and token.
+    pub conflicts: Vec<LR1Conflict<'a, T, N, A>>,
+    /// The parse table, where conflicting cells keep the shift or the first reduction.
+    pub parse_table: LR1ParseTable<'a, T, N, A>,
+    /// The grammar of the parse table.
+    pub grammar: &'a Grammar<T, N, A>,
+    /// The LR(0) state machine whose states the conflicts refer to.
+    pub state_machine: LR0StateMachine<'a, T, N, A>,
+    /// The tables of the counterexample search, built by the first counterexample.
+    pub(crate) search_tables: OnceLock<Box<SearchTables<'a, T, N>>>,
+}
+
 impl<T: Ord, N: Ord, A> Grammar<T, N, A> {
+    /// Add the augmented production `augmented_start -> start` and make it the starting state.
+    ///
+    /// The parse table of an augmented grammar accepts only after the whole input has been
+    /// reduced to the original start symbol, whichever of its alternatives matched.
+    pub fn augment(&mut self, augmented_start: N, act: A)
+    where
+        N: Clone,
+    {
+        let start = std::mem::replace(&mut self.start, augmented_start.clone());
+        self.rules.insert(
+            augmented_start,
+            vec![Rhs {
+                syms: vec![NonTerminal(start)],
+                act,
+            }],
+        );
+    }
+
     /// Create the LR(0) state machine for a grammar.
     ///
     /// # Panics
@@ -262,18 +530,17 @@ impl<T: Ord, N: Ord, A> Grammar<T, N, A> {
         state.complete_nub(
             self,
             ItemSet {
-                items: {
-                    let mut r = BTreeSet::new();
-                    r.insert(Item {
+                items: self
+                    .rules
+                    .get(&self.start)
+                    .expect("Start rule not found in grammar")
+                    .iter()
+                    .map(|rhs| Item {
                         lhs: &self.start,
-                        rhs: &self
-                            .rules
-                            .get(&self.start)
-                            .expect("Start rule not found in grammar")[0],
+                        rhs,
                         pos: 0,
-                    });
-                    r
-                },
+                    })
+                    .collect(),
             },
         );
         while finished < state.states.len() {
@@ -421,25 +688,55 @@ impl<T: Ord, N: Ord, A> Grammar<T, N, A> {
 
     /// Create an LALR(1) parse table out of the grammar.
     ///
+    /// Shift-reduce conflicts are passed to `resolve` together with the reduce rule and the
+    /// token, and are only reported if it returns `None`.
+    ///
     /// # Errors
-    /// Returns `LR1Conflict` if the grammar has shift-reduce or reduce-reduce conflicts.
+    /// Returns a `ConflictReport` with every unresolved shift-reduce or reduce-reduce conflict
+    /// of the grammar.
+    ///
+    /// # Panics
+    /// Panics if there are internal inconsistencies during table construction.
+    pub fn lalr1<ReduceFn, PriorityFn, ResolveFn>(
+        &self,
+        reduce_on: ReduceFn,
+        priority_of: PriorityFn,
+        resolve: ResolveFn,
+    ) -> Result<LR1ParseTable<'_, T, N, A>, ConflictReport<'_, T, N, A>>
+    where
+        ReduceFn: FnMut(&Rhs<T, N, A>, Option<&T>) -> bool,
+        PriorityFn: FnMut(&Rhs<T, N, A>, Option<&T>) -> i32,
+        ResolveFn: FnMut(&N, &Rhs<T, N, A>, &T) -> Option<Resolution>,
+    {
+        self.lr1(LR1Construction::Lalr, reduce_on, priority_of, resolve)
+    }
+
+    /// Create an LR(1) parse table out of the grammar, with states built by the given
+    /// construction.
+    ///
+    /// Shift-reduce conflicts are passed to `resolve` together with the reduce rule and the
+    /// token, and are only reported if it returns `None`.
+    ///
+    /// # Errors
+    /// Returns a `ConflictReport` with every unresolved shift-reduce or reduce-reduce conflict
+    /// of the grammar.
     ///
     /// # Panics
     /// Panics if there are internal inconsistencies during table construction.
     #[allow(clippy::too_many_lines)]
-    pub fn lalr1<ReduceFn, PriorityFn>(
+    pub fn lr1<ReduceFn, PriorityFn, ResolveFn>(
         &self,
+        construction: LR1Construction,
         mut reduce_on: ReduceFn,
         mut priority_of: PriorityFn,
-    ) -> Result<LR1ParseTable<'_, T, N, A>, LR1Conflict<'_, T, N, A>>
+        mut resolve: ResolveFn,
+    ) -> Result<LR1ParseTable<'_, T, N, A>, ConflictReport<'_, T, N, A>>
     where
         ReduceFn: FnMut(&Rhs<T, N, A>, Option<&T>) -> bool,
         PriorityFn: FnMut(&Rhs<T, N, A>, Option<&T>) -> i32,
+        ResolveFn: FnMut(&N, &Rhs<T, N, A>, &T) -> Option<Resolution>,
     {
-        let state_machine = self.lr0_state_machine();
-        let extended = state_machine.extended_grammar();
-        let first_sets = extended.first_sets();
-        let follow_sets = extended.follow_sets(&first_sets);
+        let (state_machine, reductions) = self.states(construction);
         let mut r = LR1ParseTable {
             states: state_machine
                 .states
@@ -469,76 +766,26 @@ impl<T: Ord, N: Ord, A> Grammar<T, N, A> {
             }
         }
 
-        // Add reductions.
-        for ((&(start_state, lhs), rhss), (&&(s2, l2), &(ref follow, eof))) in
-            extended.rules.iter().zip(follow_sets.iter())
-        {
-            debug_assert_eq!(start_state, s2);
-            debug_assert!(lhs == l2);
-
-            for &Rhs {
-                syms: _,
-                act: (end_state, rhs),
-            } in rhss
-            {
-                for &&t in follow.iter().filter(|&&&t| reduce_on(rhs, Some(t))) {
-                    match r.states[end_state].lookahead.entry(t) {
-                        btree_map::Entry::Vacant(v) => {
-                            v.insert(LRAction::Reduce(lhs, rhs));
-                        }
-                        btree_map::Entry::Occupied(mut v) => {
-                            match *v.get_mut() {
-                                LRAction::Reduce(l, r) if l == lhs && std::ptr::eq(r, rhs) => {
-                                    // The cells match, so there's no conflict.
-                                }
-                                LRAction::Reduce(ref mut l, ref mut r) => {
-                                    match priority_of(r, Some(t)).cmp(&priority_of(rhs, Some(t))) {
-                                        cmp::Ordering::Greater => {
-                                            // `r` overrides `rhs` - do nothing.
-                                        }
-                                        cmp::Ordering::Less => {
-                                            // `rhs` overrides `r`.
-                                            *l = lhs;
-                                            *r = rhs;
-                                        }
-                                        cmp::Ordering::Equal => {
-                                            // Otherwise, we have a reduce/reduce conflict.
-                                            return Err(LR1Conflict::ReduceReduce {
-                                                state: state_machine.states[end_state].0.clone(),
-                                                token: Some(t),
-                                                r1: (*l, *r),
-                                                r2: (lhs, rhs),
-                                            });
-                                        }
-                                    }
-                                }
-                                LRAction::Shift(_) => {
-                                    return Err(LR1Conflict::ShiftReduce {
-                                        state: state_machine.states[end_state].0.clone(),
-                                        token: Some(t),
-                                        rule: (lhs, rhs),
-                                    });
-                                }
-                                LRAction::Accept => {
-                                    unreachable!();
-                                }
-                            }
-                        }
-                    }
+        // Add reductions, remembering the cells made errors by conflict resolution. Conflicts
+        // are collected and keep the action already in the cell.
+        let mut errors = BTreeSet::new();
+        let mut conflicts = Vec::new();
+        for &(end_state, lhs, rhs, (ref follow, eof)) in &reductions {
+            for &t in follow.iter().filter(|&&t| reduce_on(rhs, Some(t))) {
+                if errors.contains(&(end_state, t)) {
+                    continue;
                 }
-
-                if eof && reduce_on(rhs, None) {
-                    let state = &mut r.states[end_state];
-                    if *lhs == self.start {
-                        if state.eof.is_some() {
-                            unreachable!()
-                        }
-                        state.eof = Some(LRAction::Accept);
-                    } else {
-                        match state.eof {
-                            Some(LRAction::Reduce(l, r)) if l == lhs && std::ptr::eq(r, rhs) => {}
-                            Some(LRAction::Reduce(ref mut l, ref mut r)) => {
-                                match priority_of(r, None).cmp(&priority_of(rhs, None)) {
+                match r.states[end_state].lookahead.entry(t) {
+                    btree_map::Entry::Vacant(v) => {
+                        v.insert(LRAction::Reduce(lhs, rhs));
+                    }
+                    btree_map::Entry::Occupied(mut v) => {
+                        match *v.get_mut() {
+                            LRAction::Reduce(l, r) if l == lhs && std::ptr::eq(r, rhs) => {
+                                // The cells match, so there's no conflict.
+                            }
+                            LRAction::Reduce(ref mut l, ref mut r) => {
+                                match priority_of(r, Some(t)).cmp(&priority_of(rhs, Some(t))) {
                                     cmp::Ordering::Greater => {
                                         // `r` overrides `rhs` - do nothing.
                                     }
@@ -548,37 +795,519 @@ impl<T: Ord, N: Ord, A> Grammar<T, N, A> {
                                         *r = rhs;
                                     }
                                     cmp::Ordering::Equal => {
-                                        // We have a reduce/reduce conflict.
-                                        return Err(LR1Conflict::ReduceReduce {
+                                        // Otherwise, we have a reduce/reduce conflict.
+                                        conflicts.push(LR1Conflict::ReduceReduce {
+                                            state_id: end_state,
                                             state: state_machine.states[end_state].0.clone(),
-                                            token: None,
+                                            token: Some(t),
                                             r1: (*l, *r),
                                             r2: (lhs, rhs),
                                         });
                                     }
                                 }
                             }
-                            Some(LRAction::Shift(_)) => {
-                                return Err(LR1Conflict::ShiftReduce {
-                                    state: state_machine.states[end_state].0.clone(),
-                                    token: None,
-                                    rule: (lhs, rhs),
-                                });
-                            }
-                            Some(LRAction::Accept) => {
+                            LRAction::Shift(_) => match resolve(lhs, rhs, t) {
+                                Some(Resolution::Shift) => {}
+                                Some(Resolution::Reduce) => {
+                                    *v.get_mut() = LRAction::Reduce(lhs, rhs);
+                                }
+                                Some(Resolution::Error) => {
+                                    v.remove();
+                                    errors.insert((end_state, t));
+                                }
+                                None => {
+                                    conflicts.push(LR1Conflict::ShiftReduce {
+                                        state_id: end_state,
+                                        state: state_machine.states[end_state].0.clone(),
+                                        token: Some(t),
+                                        rule: (lhs, rhs),
+                                    });
+                                }
+                            },
+                            LRAction::Accept => {
                                 unreachable!();
                             }
-                            None => {
-                                state.eof = Some(LRAction::Reduce(lhs, rhs));
+                        }
+                    }
+                }
+            }
+
+            if eof && reduce_on(rhs, None) {
+                let state = &mut r.states[end_state];
+                if *lhs == self.start {
+                    // Accepting shifts EOF, so a reduction on EOF conflicts with it.
+                    if let Some(LRAction::Reduce(l, r)) = state.eof {
+                        conflicts.push(LR1Conflict::ShiftReduce {
+                            state_id: end_state,
+                            state: state_machine.states[end_state].0.clone(),
+                            token: None,
+                            rule: (l, r),
+                        });
+                    }
+                    state.eof = Some(LRAction::Accept);
+                } else {
+                    match state.eof {
+                        Some(LRAction::Reduce(l, r)) if l == lhs && std::ptr::eq(r, rhs) => {}
+                        Some(LRAction::Reduce(ref mut l, ref mut r)) => {
+                            match priority_of(r, None).cmp(&priority_of(rhs, None)) {
+                                cmp::Ordering::Greater => {
+                                    // `r` overrides `rhs` - do nothing.
+                                }
+                                cmp::Ordering::Less => {
+                                    // `rhs` overrides `r`.
+                                    *l = lhs;
+                                    *r = rhs;
+                                }
+                                cmp::Ordering::Equal => {
+                                    // We have a reduce/reduce conflict.
+                                    conflicts.push(LR1Conflict::ReduceReduce {
+                                        state_id: end_state,
+                                        state: state_machine.states[end_state].0.clone(),
+                                        token: None,
+                                        r1: (*l, *r),
+                                        r2: (lhs, rhs),
+                                    });
+                                }
                             }
                         }
+                        Some(LRAction::Shift(_) | LRAction::Accept) => {
+                            conflicts.push(LR1Conflict::ShiftReduce {
+                                state_id: end_state,
+                                state: state_machine.states[end_state].0.clone(),
+                                token: None,
+                                rule: (lhs, rhs),
+                            });
+                        }
+                        None => {
+                            state.eof = Some(LRAction::Reduce(lhs, rhs));
+                        }
                     }
                 }
             }
         }
 
-        Ok(r)
+        if conflicts.is_empty() {
+            Ok(r)
+        } else {
+            // A rule reduced from several states is met once per state, report it only once.
+            let mut unique: Vec<LR1Conflict<'_, T, N, A>> = Vec::with_capacity(conflicts.len());
+            for conflict in conflicts {
+                if !unique.iter().any(|other| other.is_duplicate(&conflict)) {
+                    unique.push(conflict);
+                }
+            }
+            let mut conflicts = unique;
+            conflicts
+                .sort_by(|c1, c2| (c1.state_id(), c1.token()).cmp(&(c2.state_id(), c2.token())));
+            Err(ConflictReport {
+                conflicts,
+                parse_table: r,
+                grammar: self,
+                state_machine,
+                search_tables: OnceLock::new(),
+            })
+        }
+    }
+
+    /// Create the state machine of the parse table built by the given construction, whose
+    /// states have the LR(0) items of the LR(1) states.
+    pub fn state_machine(&self, construction: LR1Construction) -> LR0StateMachine<'_, T, N, A> {
+        self.states(construction).0
+    }
+
+    /// Create a GLR parse table out of the grammar, keeping every action of the conflicts that
+    /// are not resolved by `priority_of` or `resolve`.
+    pub fn glr<ReduceFn, PriorityFn, ResolveFn>(
+        &self,
+        construction: LR1Construction,
+        reduce_on: ReduceFn,
+        priority_of: PriorityFn,
+        resolve: ResolveFn,
+    ) -> GLRParseTable<'_, T, N, A>
+    where
+        ReduceFn: FnMut(&Rhs<T, N, A>, Option<&T>) -> bool,
+        PriorityFn: FnMut(&Rhs<T, N, A>, Option<&T>) -> i32,
+        ResolveFn: FnMut(&N, &Rhs<T, N, A>, &T) -> Option<Resolution>,
+    {
+        let (parse_table, conflicts) = match self.lr1(construction, reduce_on, priority_of, resolve)
+        {
+            Ok(parse_table) => (parse_table, Vec::new()),
+            Err(report) => (report.parse_table, report.conflicts),
+        };
+        let mut r = GLRParseTable {
+            states: parse_table
+                .states
+                .into_iter()
+                .map(|state| GLRState {
+                    eof: state.eof.into_iter().collect(),
+                    lookahead: state
+                        .lookahead
+                        .into_iter()
+                        .map(|(t, action)| (t, vec![action]))
+                        .collect(),
+                    goto: state.goto,
+                })
+                .collect(),
+        };
+
+        // Add the actions the conflicts did not keep in the parse table.
+        for conflict in conflicts {
+            let (state_id, token, rules) = match conflict {
+                LR1Conflict::ShiftReduce {
+                    state_id,
+                    token,
+                    rule,
+                    ..
+                } => (state_id, token, vec![rule]),
+                LR1Conflict::ReduceReduce {
+                    state_id,
+                    token,
+                    r1,
+                    r2,
+                    ..
+                } => (state_id, token, vec![r1, r2]),
+            };
+            let state = &mut r.states[state_id];
+            let actions = match token {
+                Some(t) => state.lookahead.entry(t).or_default(),
+                None => &mut state.eof,
+            };
+            for (lhs, rhs) in rules {
+                let action = LRAction::Reduce(lhs, rhs);
+                if !actions.contains(&action) {
+                    actions.push(action);
+                }
+            }
+        }
+        r
+    }
+
+    /// Find the weakest construction that builds a parse table without conflicts, or `None` if
+    /// the grammar is not LR(1), e.g. because it is ambiguous. Minimal LR(1) is never returned,
+    /// as it has conflicts exactly for the grammars that are not LR(1).
+    pub fn classify(&self) -> Option<LR1Construction> {
+        [
+            LR1Construction::Lr0,
+            LR1Construction::Slr,
+            LR1Construction::Lalr,
+            LR1Construction::Canonical,
+        ]
+        .into_iter()
+        .find(|&construction| {
+            self.lr1(construction, |_, _| true, |_, _| 0, |_, _, _| None)
+                .is_ok()
+        })
+    }
+
+    /// Create the states of the given construction with the lookaheads of their reductions.
+    fn states(
+        &self,
+        construction: LR1Construction,
+    ) -> (LR0StateMachine<'_, T, N, A>, Reductions<'_, T, N, A>) {
+        if matches!(construction, LR1Construction::Lr0 | LR1Construction::Slr) {
+            let state_machine = self.lr0_state_machine();
+            let follow_sets = if construction == LR1Construction::Slr {
+                let first_sets = self.first_sets();
+                self.follow_sets(&first_sets)
+            } else {
+                // Every terminal follows every nonterminal, only EOF follows the start symbol.
+                let terminals: BTreeSet<&T> = self
+                    .rules
+                    .values()
+                    .flatten()
+                    .flat_map(|rhs| &rhs.syms)
+                    .filter_map(|sym| match *sym {
+                        Terminal(ref t) => Some(t),
+                        NonTerminal(_) => None,
+                    })
+                    .collect();
+                self.rules
+                    .keys()
+                    .map(|lhs| {
+                        if *lhs == self.start {
+                            (lhs, (BTreeSet::new(), true))
+                        } else {
+                            (lhs, (terminals.clone(), true))
+                        }
+                    })
+                    .collect()
+            };
+            let mut reductions = Vec::new();
+            for (ix, (items, _)) in state_machine.states.iter().enumerate() {
+                for item in &items.items {
+                    if item.pos == item.rhs.syms.len() {
+                        let follow = follow_sets[item.lhs].clone();
+                        reductions.push((ix, item.lhs, item.rhs, follow));
+                    }
+                }
+            }
+            return (state_machine, reductions);
+        }
+
+        if construction == LR1Construction::Lalr {
+            let state_machine = self.lr0_state_machine();
+            let extended = state_machine.extended_grammar();
+            let first_sets = extended.first_sets();
+            let follow_sets = extended.follow_sets(&first_sets);
+            let mut reductions = Vec::new();
+            for ((&(start_state, lhs), rhss), (&&(s2, l2), follow)) in
+                extended.rules.iter().zip(follow_sets.iter())
+            {
+                debug_assert_eq!(start_state, s2);
+                debug_assert!(lhs == l2);
+                for &Rhs {
+                    act: (end_state, rhs),
+                    ..
+                } in rhss
+                {
+                    let lookahead = follow.0.iter().map(|&&t| t).collect();
+                    reductions.push((end_state, lhs, rhs, (lookahead, follow.1)));
+                }
+            }
+            return (state_machine, reductions);
+        }
+
+        let mut states = self.lr1_states();
+        if construction == LR1Construction::Minimal {
+            states = merge_states(&states);
+        }
+        let mut reductions = Vec::new();
+        for (ix, (items, _)) in states.iter().enumerate() {
+            for (item, lookahead) in items {
+                if item.pos == item.rhs.syms.len() {
+                    reductions.push((ix, item.lhs, item.rhs, lookahead.clone()));
+                }
+            }
+        }
+        let state_machine = LR0StateMachine {
+            states: states
+                .into_iter()
+                .map(|(items, transitions)| {
+                    let items = items.into_keys().collect();
+                    (ItemSet { items }, transitions)
+                })
+                .collect(),
+            start: &self.start,
+        };
+        (state_machine, reductions)
+    }
+
+    /// Create the canonical LR(1) states of the grammar.
+    ///
+    /// # Panics
+    /// Panics if the starting rule is not found in the grammar.
+    fn lr1_states<'a>(&'a self) -> LR1States<'a, T, N, A> {
+        let first_sets = self.first_sets();
+
+        // Add the items predicted by the items, with the FIRST set of the rest of their rule as
+        // lookahead, and their own lookahead if that rest is nullable.
+        let closure = |kernel: LR1Items<'a, T, N, A>| {
+            let mut items = kernel;
+            let mut to_add: VecDeque<_> = items.keys().cloned().collect();
+            while let Some(item) = to_add.pop_front() {
+                let Some(NonTerminal(n)) = item.rhs.syms.get(item.pos) else {
+                    continue;
+                };
+                let Some(rules) = self.rules.get(n) else {
+                    continue;
+                };
+                let mut lookahead = (BTreeSet::new(), true);
+                for sym in &item.rhs.syms[item.pos + 1..] {
+                    match *sym {
+                        Terminal(ref t) => {
+                            lookahead.0.insert(t);
+                            lookahead.1 = false;
+                        }
+                        NonTerminal(ref m) => {
+                            let (first, nullable) = &first_sets[m];
+                            lookahead.0.extend(first.iter().copied());
+                            lookahead.1 = *nullable;
+                        }
+                    }
+                    if !lookahead.1 {
+                        break;
+                    }
+                }
+                if lookahead.1 {
+                    let (follow, eof) = &items[&item];
+                    lookahead.0.extend(follow.iter().copied());
+                    lookahead.1 = *eof;
+                }
+                for rhs in rules {
+                    let new_item = Item {
+                        lhs: n,
+                        rhs,
+                        pos: 0,
+                    };
+                    match items.entry(new_item.clone()) {
+                        btree_map::Entry::Vacant(v) => {
+                            v.insert(lookahead.clone());
+                            to_add.push_back(new_item);
+                        }
+                        btree_map::Entry::Occupied(mut v) => {
+                            let (follow, eof) = v.get_mut();
+                            let len = follow.len();
+                            follow.extend(lookahead.0.iter().copied());
+                            if follow.len() > len || (lookahead.1 && !*eof) {
+                                *eof |= lookahead.1;
+                                to_add.push_back(new_item);
+                            }
+                        }
+                    }
+                }
+            }
+            items
+        };
+
+        let mut states: LR1States<'_, T, N, A> = vec![];
+        let mut kernels = BTreeMap::new();
+        let start = self
+            .rules
+            .get(&self.start)
+            .expect("Start rule not found in grammar")
+            .iter()
+            .map(|rhs| {
+                let item = Item {
+                    lhs: &self.start,
+                    rhs,
+                    pos: 0,
+                };
+                (item, (BTreeSet::new(), true))
+            })
+            .collect::<LR1Items<'_, T, N, A>>();
+        kernels.insert(start.clone(), 0);
+        states.push((closure(start), BTreeMap::new()));
+
+        let mut finished = 0;
+        while finished < states.len() {
+            let mut next_kernels: BTreeMap<_, LR1Items<'_, T, N, A>> = BTreeMap::new();
+            for (item, lookahead) in &states[finished].0 {
+                if let Some(sym) = item.rhs.syms.get(item.pos) {
+                    let next = Item {
+                        lhs: item.lhs,
+                        rhs: item.rhs,
+                        pos: item.pos + 1,
+                    };
+                    next_kernels
+                        .entry(sym)
+                        .or_default()
+                        .insert(next, lookahead.clone());
+                }
+            }
+            for (sym, kernel) in next_kernels {
+                let ix = if let Some(&ix) = kernels.get(&kernel) {
+                    ix
+                } else {
+                    let ix = states.len();
+                    kernels.insert(kernel.clone(), ix);
+                    states.push((closure(kernel), BTreeMap::new()));
+                    ix
+                };
+                states[finished].1.insert(sym, ix);
+            }
+            finished += 1;
+        }
+        states
+    }
+}
+
+/// Merge LR(1) states with the same LR(0) items, unless that introduces a reduce-reduce
+/// conflict, keeping the transitions of merged states consistent.
+fn merge_states<'a, T: Ord, N: Ord, A>(states: &LR1States<'a, T, N, A>) -> LR1States<'a, T, N, A> {
+    type Reduced<'r, 'a, T, N, A> = BTreeMap<Option<&'a T>, BTreeSet<&'r Item<'a, T, N, A>>>;
+
+    /// The rules reduced in a state by lookahead, EOF being `None`.
+    fn reduced<'r, 'a, T: Ord, N: Ord, A>(
+        items: &'r LR1Items<'a, T, N, A>,
+    ) -> Reduced<'r, 'a, T, N, A> {
+        let mut reduced: Reduced<'r, 'a, T, N, A> = BTreeMap::new();
+        for (item, (follow, eof)) in items {
+            if item.pos == item.rhs.syms.len() {
+                for &t in follow {
+                    reduced.entry(Some(t)).or_default().insert(item);
+                }
+                if *eof {
+                    reduced.entry(None).or_default().insert(item);
+                }
+            }
+        }
+        reduced
+    }
+
+    // Group the states with the same LR(0) items into classes, adding each state to the first
+    // class it does not introduce a new conflict into. Shift-reduce conflicts are the same for
+    // every state of a group, as they have the same shifts.
+    let mut cores = BTreeMap::new();
+    let mut class = vec![0; states.len()];
+    for (ix, (items, _)) in states.iter().enumerate() {
+        let state = reduced(items);
+        let classes: &mut Vec<Reduced<'_, 'a, T, N, A>> =
+            cores.entry(items.keys().collect::<Vec<_>>()).or_default();
+        let compatible = classes.iter().position(|union| {
+            state.iter().all(|(token, rules)| {
+                union.get(token).is_none_or(|other| {
+                    let merged: BTreeSet<_> = rules.union(other).collect();
+                    merged.len() <= 1 || merged.len() == rules.len().max(other.len())
+                })
+            })
+        });
+        let position = compatible.unwrap_or_else(|| {
+            classes.push(BTreeMap::new());
+            classes.len() - 1
+        });
+        for (token, rules) in state {
+            classes[position].entry(token).or_default().extend(rules);
+        }
+        class[ix] = position;
+    }
+    let mut ids = BTreeMap::new();
+    for (ix, (items, _)) in states.iter().enumerate() {
+        let count = ids.len();
+        class[ix] = *ids
+            .entry((items.keys().collect::<Vec<_>>(), class[ix]))
+            .or_insert(count);
+    }
+
+    // Split the classes until all states of a class go to the same class on each symbol.
+    loop {
+        let mut ids = BTreeMap::new();
+        let refined: Vec<usize> = states
+            .iter()
+            .enumerate()
+            .map(|(ix, (_, transitions))| {
+                let targets: Vec<_> = transitions
+                    .iter()
+                    .map(|(&sym, &target)| (sym, class[target]))
+                    .collect();
+                let count = ids.len();
+                *ids.entry((class[ix], targets)).or_insert(count)
+            })
+            .collect();
+        let done = ids.len() == class.iter().max().map_or(0, |&max| max + 1);
+        class = refined;
+        if done {
+            break;
+        }
+    }
+
+    // Merge the lookaheads of the states of each class. Classes are numbered in order of their
+    // first state, so the start state stays the first state.
+    let count = class.iter().max().map_or(0, |&max| max + 1);
+    let mut merged: LR1States<'a, T, N, A> = (0..count)
+        .map(|_| (BTreeMap::new(), BTreeMap::new()))
+        .collect();
+    for (ix, (items, transitions)) in states.iter().enumerate() {
+        let (merged_items, merged_transitions) = &mut merged[class[ix]];
+        for (item, (follow, eof)) in items {
+            let (merged_follow, merged_eof) = merged_items.entry(item.clone()).or_default();
+            merged_follow.extend(follow.iter().copied());
+            *merged_eof |= eof;
+        }
+        for (&sym, &target) in transitions {
+            merged_transitions.insert(sym, class[target]);
+        }
     }
+    merged
 }
 
 type ExtGrammar<'a, T, N, A> = Grammar<&'a T, (usize, &'a N), (usize, &'a Rhs<T, N, A>)>;
@@ -1,14 +1,103 @@
-use crate::common::grammar::Symbol;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::common::grammar::{Resolution, Symbol};
 use crate::common::symbol_table::{NonTerminal, Terminal};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Rule {
     pub non_terminal: NonTerminal,
     pub rhs: Vec<Symbol<Terminal, NonTerminal>>,
+
+    /// Terminal whose precedence the rule takes, set by `%prec`.
+    pub precedence: Option<Terminal>,
+
+    /// Label of the alternative, set by `:label`.
+    pub label: Option<String>,
+}
+
+/// The identity of a rule, attached to its right hand side in the parse table and passed to
+/// `Action::on_reduce` with it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Production {
+    /// Index of the rule in `GrammarRules::rules`, or the number of rules for the rule of the
+    /// augmented start symbol.
+    pub id: usize,
+
+    /// Label of the rule, if any.
+    pub label: Option<String>,
+}
+
+/// Associativity of a precedence level, set by `@left`, `@right` and `@nonassoc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssoc,
+}
+
+/// Precedence of a terminal, higher levels bind tighter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precedence {
+    pub level: usize,
+    pub associativity: Associativity,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct GrammarRules {
     pub start_symbol: NonTerminal,
+
+    /// Rules in order of their definition, identified by their index.
     pub rules: Vec<Rule>,
+
+    /// Precedence of terminals, in order of the precedence directives.
+    pub precedences: HashMap<Terminal, Precedence>,
+}
+
+impl GrammarRules {
+    /// Get the precedence of a rule, which is that of its `%prec` terminal if any, otherwise
+    /// that of the last terminal in its right hand side.
+    #[must_use]
+    pub fn rule_precedence(
+        &self,
+        non_terminal: &NonTerminal,
+        rhs: &[Symbol<Terminal, NonTerminal>],
+    ) -> Option<Precedence> {
+        let rule = self
+            .rules
+            .iter()
+            .find(|rule| &rule.non_terminal == non_terminal && rule.rhs == rhs)?;
+        let terminal = rule.precedence.as_ref().or_else(|| {
+            rule.rhs.iter().rev().find_map(|symbol| match symbol {
+                Symbol::Terminal(terminal) => Some(terminal),
+                Symbol::NonTerminal(_) => None,
+            })
+        })?;
+        self.precedences.get(terminal).copied()
+    }
+
+    /// Resolve a shift-reduce conflict between reducing by a rule and shifting a terminal.
+    ///
+    /// The higher precedence wins, on equal precedence left associativity reduces, right
+    /// associativity shifts and non associativity makes the terminal a syntax error. Returns
+    /// `None` if the rule or the terminal has no precedence.
+    #[must_use]
+    pub fn resolve(
+        &self,
+        non_terminal: &NonTerminal,
+        rhs: &[Symbol<Terminal, NonTerminal>],
+        terminal: &Terminal,
+    ) -> Option<Resolution> {
+        let rule = self.rule_precedence(non_terminal, rhs)?;
+        let terminal = self.precedences.get(terminal)?;
+        Some(match rule.level.cmp(&terminal.level) {
+            Ordering::Greater => Resolution::Reduce,
+            Ordering::Less => Resolution::Shift,
+            Ordering::Equal => match terminal.associativity {
+                Associativity::Left => Resolution::Reduce,
+                Associativity::Right => Resolution::Shift,
+                Associativity::NonAssoc => Resolution::Error,
+            },
+        })
+    }
 }
@@ -0,0 +1,198 @@
+use crate::common::parse_tree::Span;
+
+/// The unit columns are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes.
+    Byte,
+    /// Unicode scalar values, i.e. Rust `char`s.
+    #[default]
+    Char,
+    /// UTF-16 code units, as used by the Language Server Protocol.
+    Utf16,
+}
+
+/// The start offsets of the lines of an input, mapping byte offsets to 1-based lines and
+/// columns and back.
+///
+/// Lines are found by binary search, so a lookup takes logarithmic time in the number of lines.
+/// Columns are counted in a `ColumnUnit`, which takes constant time on lines of ASCII text and
+/// logarithmic time in the number of non-ASCII characters of the line otherwise.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    input: &'a str,
+    /// The offset of the first byte of every line.
+    line_starts: Vec<usize>,
+    /// The non-ASCII characters of every line, where the column units differ.
+    wide_chars: Vec<Vec<WideChar>>,
+    column_unit: ColumnUnit,
+}
+
+/// A non-ASCII character of a line, with its columns counted from 0.
+#[derive(Debug, Clone, Copy)]
+struct WideChar {
+    /// The byte offset of the character from the start of its line.
+    offset: usize,
+    len_utf8: usize,
+    len_utf16: usize,
+    char_column: usize,
+    utf16_column: usize,
+}
+
+impl WideChar {
+    /// The column of the character in a unit other than bytes.
+    const fn column(&self, column_unit: ColumnUnit) -> usize {
+        match column_unit {
+            ColumnUnit::Utf16 => self.utf16_column,
+            _ => self.char_column,
+        }
+    }
+
+    /// The column after the character in a unit other than bytes.
+    const fn end_column(&self, column_unit: ColumnUnit) -> usize {
+        match column_unit {
+            ColumnUnit::Utf16 => self.utf16_column + self.len_utf16,
+            _ => self.char_column + 1,
+        }
+    }
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of the input in a single pass.
+    #[must_use]
+    pub fn new(input: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        let mut wide_chars = vec![Vec::new()];
+        let (mut char_column, mut utf16_column) = (0, 0);
+        for (offset, char) in input.char_indices() {
+            if char == '\n' {
+                line_starts.push(offset + 1);
+                wide_chars.push(Vec::new());
+                (char_column, utf16_column) = (0, 0);
+                continue;
+            }
+            if !char.is_ascii() {
+                let line = wide_chars.len() - 1;
+                wide_chars[line].push(WideChar {
+                    offset: offset - line_starts[line],
+                    len_utf8: char.len_utf8(),
+                    len_utf16: char.len_utf16(),
+                    char_column,
+                    utf16_column,
+                });
+            }
+            char_column += 1;
+            utf16_column += char.len_utf16();
+        }
+        Self {
+            input,
+            line_starts,
+            wide_chars,
+            column_unit: ColumnUnit::default(),
+        }
+    }
+
+    /// Sets the unit of the columns, characters by default.
+    #[must_use]
+    pub const fn with_column_unit(mut self, column_unit: ColumnUnit) -> Self {
+        self.column_unit = column_unit;
+        self
+    }
+
+    /// The number of lines, counting the one after a trailing line feed.
+    #[must_use]
+    pub const fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The line and column of a byte offset, clamped to the end of the input.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the offset is not at a character boundary.
+    #[must_use]
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.input.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        (line, self.column_of(line, offset, self.column_unit))
+    }
+
+    /// The column of a byte offset in the given unit, clamped to the end of the input.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the offset is not at a character boundary.
+    #[must_use]
+    pub fn column(&self, offset: usize, column_unit: ColumnUnit) -> usize {
+        let offset = offset.min(self.input.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        self.column_of(line, offset, column_unit)
+    }
+
+    /// The column of a byte offset on a line, from the last non-ASCII character before it.
+    fn column_of(&self, line: usize, offset: usize, column_unit: ColumnUnit) -> usize {
+        assert!(
+            self.input.is_char_boundary(offset),
+            "Offset {offset} is not at a character boundary"
+        );
+        let offset = offset - self.line_starts[line - 1];
+        let wide_chars = &self.wide_chars[line - 1];
+        let index = wide_chars.partition_point(|wide_char| wide_char.offset < offset);
+        let column = match (column_unit, index.checked_sub(1)) {
+            (ColumnUnit::Byte, _) | (_, None) => offset,
+            (_, Some(index)) => {
+                let wide_char = &wide_chars[index];
+                let end = wide_char.offset + wide_char.len_utf8;
+                wide_char.end_column(column_unit) + offset - end
+            }
+        };
+        column + 1
+    }
+
+    /// The byte offset of a line and column, or `None` if the line does not exist, is shorter
+    /// or the column is within a character. The column after the last character of a line is
+    /// its end.
+    #[must_use]
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let text = self.line(line)?;
+        let column = column.checked_sub(1)?;
+        let wide_chars = &self.wide_chars[line - 1];
+        let offset = if self.column_unit == ColumnUnit::Byte {
+            column
+        } else {
+            let index =
+                wide_chars.partition_point(|wide_char| wide_char.column(self.column_unit) < column);
+            match index.checked_sub(1) {
+                None => column,
+                Some(index) => {
+                    let wide_char = &wide_chars[index];
+                    let end_column = wide_char.end_column(self.column_unit);
+                    if column < end_column {
+                        return None;
+                    }
+                    wide_char.offset + wide_char.len_utf8 + column - end_column
+                }
+            }
+        };
+        (offset <= text.len() && text.is_char_boundary(offset)).then_some(start + offset)
+    }
+
+    /// The text of a line, without its line feed.
+    #[must_use]
+    pub fn line(&self, line: usize) -> Option<&'a str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.input.len(), |&next| next - 1);
+        Some(&self.input[start..end])
+    }
+
+    /// The span of a byte range, located at its start.
+    #[must_use]
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let (line, column) = self.line_col(start);
+        Span::new(start, end, line, column)
+    }
+}
@@ -1,7 +1,13 @@
 pub mod action;
+pub mod counterexample;
+pub mod dot;
 pub mod grammar;
 pub mod grammar_rules;
+pub mod line_index;
+pub mod parse_forest;
 pub mod parse_table;
 pub mod parse_tree;
+pub mod report;
 pub mod symbol_table;
 pub mod token_rules;
+pub mod tree_rules;
@@ -0,0 +1,178 @@
+use std::collections::BTreeSet;
+
+use relex::Token;
+
+use crate::common::action::Action;
+use crate::common::grammar::Rhs;
+use crate::common::grammar_rules::Production;
+use crate::common::parse_tree::{ParseTreeNode, Span};
+use crate::common::symbol_table::{NonTerminal, Terminal};
+
+/// A shared packed parse forest, holding every derivation of an input.
+///
+/// Nonterminal nodes are shared by all derivations deriving the same nonterminal from the
+/// same tokens, and pack one family of children for each way to derive it.
+#[derive(Debug)]
+pub struct ParseForest<'a> {
+    pub nodes: Vec<ForestNode<'a>>,
+    /// The node of the start symbol deriving the whole input.
+    pub root: usize,
+}
+
+/// Parse forest node.
+#[derive(Debug)]
+pub enum ForestNode<'a> {
+    Terminal {
+        token: Terminal,
+        text: &'a str,
+        span: Span,
+    },
+    NonTerminal {
+        symbol: NonTerminal,
+        /// The index of the first token derived by the node.
+        start: usize,
+        /// The index after the last token derived by the node.
+        end: usize,
+        /// The span of the derived tokens, or the empty span before the next token.
+        span: Span,
+        families: Vec<Family<'a>>,
+    },
+}
+
+/// One derivation of a nonterminal node, reducing its children by a rule.
+#[derive(Debug)]
+pub struct Family<'a> {
+    pub rhs: &'a Rhs<Terminal, NonTerminal, Production>,
+    pub children: Vec<usize>,
+}
+
+impl ForestNode<'_> {
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::Terminal { span, .. } | Self::NonTerminal { span, .. } => *span,
+        }
+    }
+}
+
+impl<'a> ParseForest<'a> {
+    /// Check whether the input has more than one derivation.
+    #[must_use]
+    pub fn is_ambiguous(&self) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut to_visit = vec![self.root];
+        while let Some(node) = to_visit.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            if let ForestNode::NonTerminal { families, .. } = &self.nodes[node] {
+                if families.len() > 1 {
+                    return true;
+                }
+                to_visit.extend(families.iter().flat_map(|family| &family.children));
+            }
+        }
+        false
+    }
+
+    /// Unpack every derivation into a parse tree. Derivations of cyclic grammars which derive a
+    /// node from itself are skipped.
+    #[must_use]
+    pub fn trees(&self) -> Vec<ParseTreeNode> {
+        self.trees_of(self.root, &mut Vec::new())
+    }
+
+    /// Unpack every derivation of a node, which does not contain a node of the path to it.
+    fn trees_of(&self, node: usize, path: &mut Vec<usize>) -> Vec<ParseTreeNode> {
+        match &self.nodes[node] {
+            ForestNode::Terminal { token, text, span } => {
+                vec![ParseTreeNode::terminal(
+                    token.clone(),
+                    (*text).to_string(),
+                    *span,
+                )]
+            }
+            ForestNode::NonTerminal {
+                symbol,
+                span,
+                families,
+                ..
+            } => {
+                path.push(node);
+                let mut trees = Vec::new();
+                for family in families {
+                    if family.children.iter().any(|child| path.contains(child)) {
+                        continue;
+                    }
+                    let mut combinations: Vec<Vec<ParseTreeNode>> = vec![Vec::new()];
+                    for &child in &family.children {
+                        let subtrees = self.trees_of(child, path);
+                        combinations = combinations
+                            .iter()
+                            .flat_map(|prefix| {
+                                subtrees.iter().map(|subtree| {
+                                    let mut children = prefix.clone();
+                                    children.push(subtree.clone());
+                                    children
+                                })
+                            })
+                            .collect();
+                    }
+                    trees.extend(combinations.into_iter().map(|children| {
+                        ParseTreeNode::non_terminal(symbol.clone(), children, *span)
+                    }));
+                }
+                path.pop();
+                trees
+            }
+        }
+    }
+
+    /// Drive a semantic action with the first derivation of the forest, as if it was parsed by
+    /// an LR parser.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a node only has derivations containing the node itself.
+    pub fn replay<A: Action>(&self, action: &mut A) -> A::ParseResult {
+        self.replay_node(self.root, action, &mut Vec::new());
+        action.on_accept()
+    }
+
+    /// Shift the tokens and reduce the rules of the first derivation of a node, which does not
+    /// contain a node of the path to it.
+    fn replay_node<A: Action>(&self, node: usize, action: &mut A, path: &mut Vec<usize>) {
+        match &self.nodes[node] {
+            ForestNode::Terminal { token, text, span } => action.on_shift(
+                Token {
+                    kind: token.clone(),
+                    start: span.start,
+                    end: span.end,
+                    text,
+                    skip: false,
+                    next: None,
+                    skipped: Vec::new(),
+                    captures: None,
+                },
+                *span,
+            ),
+            ForestNode::NonTerminal {
+                symbol,
+                span,
+                families,
+                ..
+            } => {
+                path.push(node);
+                let family = families
+                    .iter()
+                    .find(|family| !family.children.iter().any(|child| path.contains(child)))
+                    .expect("Parse forest node has no acyclic derivation");
+                for &child in &family.children {
+                    self.replay_node(child, action, path);
+                }
+                action.on_reduce(symbol, family.rhs, *span);
+                path.pop();
+            }
+        }
+    }
+}
@@ -1,51 +1,204 @@
-use crate::common::grammar::{Grammar, LR1ParseTable, Rhs};
-use crate::common::grammar_rules::GrammarRules;
+use std::sync::Arc;
+
+use crate::common::grammar::{
+    ConflictReport, GLRParseTable, Grammar, LR1Construction, LR1ParseTable, Rhs,
+};
+use crate::common::grammar_rules::{GrammarRules, Production};
+use crate::common::report::Automaton;
 use crate::common::symbol_table::{NonTerminal, Terminal};
 
 pub struct ParseTable {
-    pub parse_table: LR1ParseTable<'static, Terminal, NonTerminal, ()>,
+    pub parse_table: LR1ParseTable<'static, Terminal, NonTerminal, Production>,
+
+    /// The augmented grammar the parse table was built from.
+    pub grammar: &'static Grammar<Terminal, NonTerminal, Production>,
+
+    /// The construction of the states of the parse table.
+    pub construction: LR1Construction,
 }
 
 impl ParseTable {
-    /// Creates a new `ParseTable` from grammar rules.
+    /// Creates a new LALR(1) `ParseTable` from grammar rules.
     ///
     /// # Panics
     ///
-    /// This function will panic if the grammar is not LALR(1) and a conflict is detected.
+    /// This function will panic if the grammar is not LALR(1) and a conflict is detected that
+    /// is not resolved by the precedence of the grammar rules.
     pub fn new<ReduceFn, PriorityFn>(
         grammar_rules: &GrammarRules,
         reduce_on: ReduceFn,
         priority_of: PriorityFn,
     ) -> Self
     where
-        ReduceFn: FnMut(&Rhs<Terminal, NonTerminal, ()>, Option<&Terminal>) -> bool,
-        PriorityFn: FnMut(&Rhs<Terminal, NonTerminal, ()>, Option<&Terminal>) -> i32,
+        ReduceFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> bool,
+        PriorityFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> i32,
     {
-        let mut grammar: Grammar<Terminal, NonTerminal, ()> = Grammar {
-            rules: std::collections::BTreeMap::new(),
-            start: grammar_rules.start_symbol.clone(),
-        };
-
-        for rule in &grammar_rules.rules {
-            // The entire rule.rhs should be one production, not individual symbols
-            grammar
-                .rules
-                .entry(rule.non_terminal.clone())
-                .or_default()
-                .push(Rhs {
-                    syms: rule.rhs.clone(),
-                    act: (),
-                });
-        }
+        Self::with_construction(grammar_rules, LR1Construction::Lalr, reduce_on, priority_of)
+    }
 
-        let grammar_ref: &'static Grammar<Terminal, NonTerminal, ()> = Box::leak(Box::new(grammar));
-        let parse_table = match grammar_ref.lalr1(reduce_on, priority_of) {
+    /// Creates a new `ParseTable` from grammar rules, with states built by the given
+    /// construction.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a conflict is detected that is not resolved by the
+    /// precedence of the grammar rules.
+    pub fn with_construction<ReduceFn, PriorityFn>(
+        grammar_rules: &GrammarRules,
+        construction: LR1Construction,
+        reduce_on: ReduceFn,
+        priority_of: PriorityFn,
+    ) -> Self
+    where
+        ReduceFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> bool,
+        PriorityFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> i32,
+    {
+        match Self::try_with_construction(grammar_rules, construction, reduce_on, priority_of) {
             Ok(parse_table) => parse_table,
-            Err(conflict) => {
-                panic!("Grammar is not LALR(1), conflict detected: {conflict:?}");
+            Err(report) => {
+                panic!("Grammar is not {construction}, {report}");
             }
-        };
+        }
+    }
 
-        Self { parse_table }
+    /// Creates a new LALR(1) `ParseTable` from grammar rules, reporting all conflicts at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConflictReport` with every conflict that is not resolved by the precedence
+    /// of the grammar rules.
+    pub fn try_new<ReduceFn, PriorityFn>(
+        grammar_rules: &GrammarRules,
+        reduce_on: ReduceFn,
+        priority_of: PriorityFn,
+    ) -> Result<Self, ConflictReport<'static, Terminal, NonTerminal, Production>>
+    where
+        ReduceFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> bool,
+        PriorityFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> i32,
+    {
+        Self::try_with_construction(grammar_rules, LR1Construction::Lalr, reduce_on, priority_of)
     }
+
+    /// Creates a new `ParseTable` from grammar rules, with states built by the given
+    /// construction, reporting all conflicts at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConflictReport` with every conflict that is not resolved by the precedence
+    /// of the grammar rules.
+    pub fn try_with_construction<ReduceFn, PriorityFn>(
+        grammar_rules: &GrammarRules,
+        construction: LR1Construction,
+        reduce_on: ReduceFn,
+        priority_of: PriorityFn,
+    ) -> Result<Self, ConflictReport<'static, Terminal, NonTerminal, Production>>
+    where
+        ReduceFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> bool,
+        PriorityFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> i32,
+    {
+        let grammar_ref: &'static Grammar<Terminal, NonTerminal, Production> =
+            Box::leak(Box::new(augmented_grammar(grammar_rules)));
+        let parse_table =
+            grammar_ref.lr1(construction, reduce_on, priority_of, |lhs, rhs, token| {
+                grammar_rules.resolve(lhs, &rhs.syms, token)
+            })?;
+
+        Ok(Self {
+            parse_table,
+            grammar: grammar_ref,
+            construction,
+        })
+    }
+
+    /// Creates an LALR(1) GLR parse table from grammar rules, which keeps every action of the
+    /// conflicts that are not resolved by the precedence of the grammar rules, so that
+    /// ambiguous grammars can be parsed by a `GLRParser`.
+    #[must_use]
+    pub fn glr<ReduceFn, PriorityFn>(
+        grammar_rules: &GrammarRules,
+        reduce_on: ReduceFn,
+        priority_of: PriorityFn,
+    ) -> GLRParseTable<'static, Terminal, NonTerminal, Production>
+    where
+        ReduceFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> bool,
+        PriorityFn: FnMut(&Rhs<Terminal, NonTerminal, Production>, Option<&Terminal>) -> i32,
+    {
+        let grammar_ref: &'static Grammar<Terminal, NonTerminal, Production> =
+            Box::leak(Box::new(augmented_grammar(grammar_rules)));
+        grammar_ref.glr(
+            LR1Construction::Lalr,
+            reduce_on,
+            priority_of,
+            |lhs, rhs, token| grammar_rules.resolve(lhs, &rhs.syms, token),
+        )
+    }
+
+    /// Finds the weakest construction that builds a parse table for the grammar rules without
+    /// conflicts, ignoring precedence, or `None` if the grammar is not LR(1).
+    #[must_use]
+    pub fn classify(grammar_rules: &GrammarRules) -> Option<LR1Construction> {
+        augmented_grammar(grammar_rules).classify()
+    }
+
+    /// Describes every state of the parse table with its kernel items and actions, like the
+    /// `.output` file of Bison.
+    #[must_use]
+    pub fn report(&self) -> String {
+        let state_machine = self.grammar.state_machine(self.construction);
+        Automaton {
+            grammar: self.grammar,
+            state_machine: &state_machine,
+            parse_table: &self.parse_table,
+            conflicts: &[],
+        }
+        .to_string()
+    }
+
+    /// Exports the states of the parse table, as built by its construction, as a Graphviz DOT
+    /// graph.
+    #[must_use]
+    pub fn dot(&self) -> String {
+        let state_machine = self.grammar.state_machine(self.construction);
+        Automaton {
+            grammar: self.grammar,
+            state_machine: &state_machine,
+            parse_table: &self.parse_table,
+            conflicts: &[],
+        }
+        .dot()
+    }
+}
+
+/// Converts grammar rules into a grammar augmented with `<START> -> start`, so that the parser
+/// accepts only after reducing the whole input to the start symbol. Every right hand side
+/// carries the `Production` of its rule.
+fn augmented_grammar(grammar_rules: &GrammarRules) -> Grammar<Terminal, NonTerminal, Production> {
+    let mut grammar: Grammar<Terminal, NonTerminal, Production> = Grammar {
+        rules: std::collections::BTreeMap::new(),
+        start: grammar_rules.start_symbol.clone(),
+    };
+
+    for (id, rule) in grammar_rules.rules.iter().enumerate() {
+        // The entire rule.rhs should be one production, not individual symbols
+        grammar
+            .rules
+            .entry(rule.non_terminal.clone())
+            .or_default()
+            .push(Rhs {
+                syms: rule.rhs.clone(),
+                act: Production {
+                    id,
+                    label: rule.label.clone(),
+                },
+            });
+    }
+
+    grammar.augment(
+        NonTerminal(Arc::from("<START>")),
+        Production {
+            id: grammar_rules.rules.len(),
+            label: None,
+        },
+    );
+    grammar
 }
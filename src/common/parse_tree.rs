@@ -1,5 +1,6 @@
 use std::fmt::{self, Display};
 
+use crate::common::line_index::{ColumnUnit, LineIndex};
 use crate::common::symbol_table::{NonTerminal, Terminal};
 
 /// Source location information.
@@ -16,6 +17,9 @@ pub struct Span {
 pub struct ParseError {
     pub message: String,
     pub span: Option<Span>,
+
+    /// Terminals acceptable at the error, `<EOF>` if the input could end.
+    pub expected: Vec<Terminal>,
 }
 
 /// Parse tree node.
@@ -35,10 +39,15 @@ pub enum ParseTreeNode {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Symbol {
-    Literal(String),    // Literals like "="
-    Regex(String),      // Regexes like /[a-z]+/
-    Identifier(String), // Identifiers like "expression"
-    Epsilon,            // Empty production
+    Literal(String),              // Literals like "="
+    Regex(String),                // Regexes like /[a-z]+/
+    Identifier(String),           // Identifiers like "expression"
+    Group(Vec<Vec<Symbol>>),      // Groups like ( a | b )
+    Optional(Vec<Vec<Symbol>>),   // Optionals like [ a ]
+    Repetition(Vec<Vec<Symbol>>), // Repetitions like { a }
+    Precedence(String),           // Precedence overrides like %prec UMINUS
+    Label(String),                // Labels of alternatives like :add
+    Epsilon,                      // Empty production
 }
 
 impl Span {
@@ -51,6 +60,34 @@ impl Span {
             column,
         }
     }
+
+    /// The span from the start of this span to the end of another.
+    #[must_use]
+    pub const fn join(self, other: Self) -> Self {
+        Self {
+            end: other.end,
+            ..self
+        }
+    }
+
+    /// The span with its column counted in another unit, e.g. in UTF-16 code units for the
+    /// Language Server Protocol.
+    #[must_use]
+    pub fn with_column_unit(self, line_index: &LineIndex<'_>, column_unit: ColumnUnit) -> Self {
+        Self {
+            column: line_index.column(self.start, column_unit),
+            ..self
+        }
+    }
+
+    /// The empty span at the start of this span, e.g. of an empty reduction before a token.
+    #[must_use]
+    pub const fn at_start(self) -> Self {
+        Self {
+            end: self.start,
+            ..self
+        }
+    }
 }
 
 impl ParseTreeNode {
@@ -72,6 +109,24 @@ impl ParseTreeNode {
         }
     }
 
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::Terminal { span, .. } | Self::NonTerminal { span, .. } => *span,
+        }
+    }
+
+    /// The span of a sequence of nodes, from the start of the first to the end of the last.
+    /// Without nodes, it is the empty span at the start of the input.
+    #[must_use]
+    pub fn span_of(nodes: &[Self]) -> Span {
+        match nodes {
+            [] => Span::new(0, 0, 1, 1),
+            [first, .., last] => first.span().join(last.span()),
+            [node] => node.span(),
+        }
+    }
+
     #[must_use]
     pub const fn is_empty(&self) -> bool {
         match self {
@@ -121,11 +176,58 @@ impl Display for Symbol {
             Self::Literal(str) | Self::Regex(str) | Self::Identifier(str) => {
                 write!(f, "{str}")
             }
+            Self::Group(alternatives) => write_alternatives(f, "(", alternatives, ")"),
+            Self::Optional(alternatives) => write_alternatives(f, "[", alternatives, "]"),
+            Self::Repetition(alternatives) => write_alternatives(f, "{", alternatives, "}"),
+            Self::Precedence(str) => write!(f, "%prec {str}"),
+            Self::Label(str) => write!(f, ":{str}"),
             Self::Epsilon => write!(f, "EPSILON"),
         }
     }
 }
 
+/// Write EBNF alternatives enclosed in the given brackets, e.g. `( a b | c )`.
+fn write_alternatives(
+    f: &mut fmt::Formatter<'_>,
+    open: &str,
+    alternatives: &[Vec<Symbol>],
+    close: &str,
+) -> fmt::Result {
+    write!(f, "{open}")?;
+    for (index, symbols) in alternatives.iter().enumerate() {
+        if index > 0 {
+            write!(f, " |")?;
+        }
+        for symbol in symbols {
+            write!(f, " {symbol}")?;
+        }
+    }
+    write!(f, " {close}")
+}
+
+impl ParseError {
+    /// Creates a syntax error for an unexpected terminal, with a message listing the expected
+    /// terminals, e.g. `expected one of IDENTIFIER, "(" but found "|"`.
+    #[must_use]
+    pub fn unexpected(found: &Terminal, expected: &[Terminal], span: Span) -> Self {
+        let list = expected
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = match expected {
+            [] => format!("unexpected {found}"),
+            [_] => format!("expected {list} but found {found}"),
+            _ => format!("expected one of {list} but found {found}"),
+        };
+        Self {
+            message,
+            span: Some(span),
+            expected: expected.to_vec(),
+        }
+    }
+}
+
 impl Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(span) = self.span {
@@ -0,0 +1,142 @@
+use std::fmt::{self, Display};
+
+use crate::common::grammar::{
+    ConflictReport, Grammar, LR0StateMachine, LR1Conflict, LR1ParseTable, LRAction,
+};
+
+/// A human-readable description of an LR(1) parse table and the LR(0) state machine it was
+/// built from, like the `.output` file of Bison.
+pub struct Automaton<'r, 'a, T: 'a, N: 'a, A: 'a> {
+    pub grammar: &'a Grammar<T, N, A>,
+    pub state_machine: &'r LR0StateMachine<'a, T, N, A>,
+    pub parse_table: &'r LR1ParseTable<'a, T, N, A>,
+    pub conflicts: &'r [LR1Conflict<'a, T, N, A>],
+}
+
+impl<T: Ord + Display, N: Ord + Display, A> Display for Automaton<'_, '_, T, N, A> {
+    /// Format the grammar followed by every state with its kernel items, its actions and its
+    /// conflicts.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Grammar")?;
+        writeln!(f)?;
+        for (lhs, rhss) in &self.grammar.rules {
+            for rhs in rhss {
+                writeln!(f, "  {lhs} -> {rhs}")?;
+            }
+        }
+
+        for (index, ((items, _), state)) in self
+            .state_machine
+            .states
+            .iter()
+            .zip(&self.parse_table.states)
+            .enumerate()
+        {
+            writeln!(f)?;
+            writeln!(f, "State {index}")?;
+            writeln!(f)?;
+
+            // Closure items are implied by the kernel items.
+            for item in items
+                .items
+                .iter()
+                .filter(|item| item.pos > 0 || *item.lhs == self.grammar.start)
+            {
+                writeln!(f, "  {item}")?;
+            }
+
+            let mut actions: Vec<(String, String)> = state
+                .lookahead
+                .iter()
+                .map(|(token, action)| (token.to_string(), describe(action)))
+                .collect();
+            if let Some(action) = &state.eof {
+                actions.push(("<EOF>".to_string(), describe(action)));
+            }
+            actions.extend(state.goto.iter().map(|(non_terminal, target)| {
+                (non_terminal.to_string(), format!("go to state {target}"))
+            }));
+            if !actions.is_empty() {
+                writeln!(f)?;
+                let width = actions.iter().map(|(symbol, _)| symbol.len()).max();
+                for (symbol, action) in &actions {
+                    writeln!(f, "  {symbol:width$}  {action}", width = width.unwrap_or(0))?;
+                }
+            }
+
+            let mut conflicts = self
+                .conflicts
+                .iter()
+                .filter(|conflict| conflict.state_id() == index)
+                .peekable();
+            if conflicts.peek().is_some() {
+                writeln!(f)?;
+            }
+            for conflict in conflicts {
+                let token = conflict
+                    .token()
+                    .map_or_else(|| "<EOF>".to_string(), ToString::to_string);
+                match conflict {
+                    LR1Conflict::ShiftReduce { rule, .. } => writeln!(
+                        f,
+                        "  shift/reduce conflict on {token} with {} -> {}",
+                        rule.0, rule.1
+                    )?,
+                    LR1Conflict::ReduceReduce { r1, r2, .. } => writeln!(
+                        f,
+                        "  reduce/reduce conflict on {token} between {} -> {} and {} -> {}",
+                        r1.0, r1.1, r2.0, r2.1
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Describe a parse table action.
+fn describe<T: Display, N: Display, A>(action: &LRAction<'_, T, N, A>) -> String {
+    match action {
+        LRAction::Shift(target) => format!("shift, and go to state {target}"),
+        LRAction::Reduce(lhs, rhs) => format!("reduce using {lhs} -> {rhs}"),
+        LRAction::Accept => "accept".to_string(),
+    }
+}
+
+impl<'a, T: Ord, N: Ord, A> ConflictReport<'a, T, N, A> {
+    /// The automaton of the report, with the conflicts shown in their states.
+    #[must_use]
+    pub fn automaton(&self) -> Automaton<'_, 'a, T, N, A> {
+        Automaton {
+            grammar: self.grammar,
+            state_machine: &self.state_machine,
+            parse_table: &self.parse_table,
+            conflicts: &self.conflicts,
+        }
+    }
+}
+
+impl<T: Ord + Display, N: Ord + Display, A> Display for ConflictReport<'_, T, N, A> {
+    /// Format a summary of the conflicts, then every conflict with its counterexample.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shift_reduce = self
+            .conflicts
+            .iter()
+            .filter(|conflict| matches!(conflict, LR1Conflict::ShiftReduce { .. }))
+            .count();
+        let plural = if self.conflicts.len() == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} conflict{plural}: {shift_reduce} shift/reduce, {} reduce/reduce",
+            self.conflicts.len(),
+            self.conflicts.len() - shift_reduce
+        )?;
+        for conflict in &self.conflicts {
+            write!(f, "\n\n{conflict}")?;
+            if let Some(counterexample) = self.counterexample(conflict) {
+                write!(f, "\n{counterexample}")?;
+            }
+        }
+        Ok(())
+    }
+}
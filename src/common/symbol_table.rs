@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display};
 use std::sync::Arc;
 
 use relex::TokenKind;
@@ -56,6 +57,46 @@ impl SymbolTable {
     }
 }
 
+impl Terminal {
+    /// The reserved `error` terminal, which productions use to recover from syntax errors.
+    #[must_use]
+    pub fn error() -> Self {
+        Self(Arc::from("error"))
+    }
+}
+
+impl Display for Terminal {
+    /// Format the terminal by its name, quoting names of literals such as `"+"`, but not
+    /// reserved names such as `<EOF>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let is_reserved = self
+            .0
+            .strip_prefix('<')
+            .and_then(|name| name.strip_suffix('>'))
+            .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase()));
+        let is_identifier = self
+            .0
+            .chars()
+            .next()
+            .is_some_and(|char| !char.is_ascii_digit())
+            && self
+                .0
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_');
+        if is_reserved || is_identifier {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "{:?}", self.0)
+        }
+    }
+}
+
+impl Display for NonTerminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl TokenKind for Terminal {
     fn unrecognized() -> Self {
         Self(Arc::from("<UNRECOGNIZED>"))
@@ -1,6 +1,17 @@
 use crate::common::symbol_table::Terminal;
 
-pub type TokenRules = Vec<Rule>;
+/// Token rules together with the lexical directives of a grammar.
+#[derive(Debug, Clone, Default)]
+pub struct TokenRules {
+    /// Rules for the tokens, the first matching rule wins.
+    pub rules: Vec<Rule>,
+
+    /// Regex pattern for comments, set by `@comment`.
+    pub comment: Option<String>,
+
+    /// Implicit whitespace skipped between any two tokens, set by `@whitespace`.
+    pub whitespace: Whitespace,
+}
 
 #[derive(Debug, Clone)]
 pub struct Rule {
@@ -8,3 +19,30 @@ pub struct Rule {
     pub regex: String,
     pub skip: bool,
 }
+
+/// Implicit whitespace, following DHParser's `@whitespace` presets.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Whitespace {
+    /// Spaces and tabs only, line feeds are significant.
+    Horizontal,
+    /// Spaces and tabs with at most a single line feed.
+    Linefeed,
+    /// Any whitespace, including line feeds.
+    #[default]
+    Vertical,
+    /// A custom regex pattern, which must not match the empty string.
+    Custom(String),
+}
+
+impl Whitespace {
+    /// Get the regex pattern matching this whitespace.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        match self {
+            Self::Horizontal => r"[\t ]+",
+            Self::Linefeed => r"[\t ]+\n?[\t ]*|\n[\t ]*",
+            Self::Vertical => r"\s+",
+            Self::Custom(pattern) => pattern,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+
+use crate::common::parse_tree::ParseTreeNode;
+use crate::common::symbol_table::{NonTerminal, Terminal};
+
+/// Rules for shaping the parse tree, set by the `@hide` and `@drop` directives.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TreeRules {
+    /// Non terminals whose children are spliced into their parent.
+    pub hide: HashSet<NonTerminal>,
+
+    /// Terminals removed from the tree.
+    pub drop_terminals: HashSet<Terminal>,
+
+    /// Non terminals removed from the tree together with their children.
+    pub drop_non_terminals: HashSet<NonTerminal>,
+}
+
+impl TreeRules {
+    /// Shape the children of a new node, removing dropped nodes and splicing hidden ones.
+    ///
+    /// Hidden children have already been shaped when they were built, so splicing them is
+    /// enough to hide nested layers as well.
+    #[must_use]
+    pub fn shape(&self, children: Vec<ParseTreeNode>) -> Vec<ParseTreeNode> {
+        let mut shaped = Vec::with_capacity(children.len());
+        for child in children {
+            match child {
+                ParseTreeNode::Terminal { ref token, .. }
+                    if self.drop_terminals.contains(token) => {}
+                ParseTreeNode::NonTerminal { ref symbol, .. }
+                    if self.drop_non_terminals.contains(symbol) => {}
+                ParseTreeNode::NonTerminal {
+                    ref symbol,
+                    children,
+                    ..
+                } if self.hide.contains(symbol) => shaped.extend(children),
+                child => shaped.push(child),
+            }
+        }
+        shaped
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use relex::TokenKind;
+
+use crate::common::grammar::{GLRParseTable, LRAction, Rhs};
+use crate::common::grammar_rules::Production;
+use crate::common::parse_forest::{Family, ForestNode, ParseForest};
+use crate::common::parse_tree::{ParseError, Span};
+use crate::common::symbol_table::{NonTerminal, Terminal};
+use crate::compiler::lexer::{LocatedToken, next_token};
+use crate::error::Error;
+
+/// A node of the graph structured stack, with edges to the nodes below it labeled by the
+/// parse forest node shifted on top of them.
+struct StackNode {
+    state: usize,
+    /// The number of tokens shifted before the node.
+    level: usize,
+    edges: Vec<(usize, usize)>,
+}
+
+/// The parse forest under construction, sharing nonterminal nodes by symbol and extent.
+struct ForestBuilder<'a> {
+    nodes: Vec<ForestNode<'a>>,
+    non_terminals: BTreeMap<(&'a NonTerminal, usize, usize), usize>,
+}
+
+impl<'a> ForestBuilder<'a> {
+    /// Add a derivation of a nonterminal before the given lookahead, returning its node.
+    fn non_terminal(
+        &mut self,
+        symbol: &'a NonTerminal,
+        rhs: &'a Rhs<Terminal, NonTerminal, Production>,
+        start: usize,
+        end: usize,
+        lookahead: Span,
+        children: Vec<usize>,
+    ) -> usize {
+        let span = match children.as_slice() {
+            [] => lookahead.at_start(),
+            [first, .., last] => self.nodes[*first].span().join(self.nodes[*last].span()),
+            [child] => self.nodes[*child].span(),
+        };
+        let nodes = &mut self.nodes;
+        let node = *self
+            .non_terminals
+            .entry((symbol, start, end))
+            .or_insert_with(|| {
+                nodes.push(ForestNode::NonTerminal {
+                    symbol: symbol.clone(),
+                    start,
+                    end,
+                    span,
+                    families: Vec::new(),
+                });
+                nodes.len() - 1
+            });
+        if let ForestNode::NonTerminal { families, .. } = &mut self.nodes[node]
+            && !families
+                .iter()
+                .any(|family| std::ptr::eq(family.rhs, rhs) && family.children == children)
+        {
+            families.push(Family { rhs, children });
+        }
+        node
+    }
+}
+
+pub struct GLRParser<'a> {
+    parse_table: &'a GLRParseTable<'a, Terminal, NonTerminal, Production>,
+}
+
+impl<'a> GLRParser<'a> {
+    #[must_use]
+    pub const fn new(
+        parse_table: &'a GLRParseTable<'a, Terminal, NonTerminal, Production>,
+    ) -> Self {
+        Self { parse_table }
+    }
+
+    /// Parses the input token stream with a graph structured stack, following every action of
+    /// conflicting cells, and returns the parse forest of all derivations.
+    ///
+    /// # Errors
+    ///
+    /// Returns a syntax error at the first token no stack can shift, or the lexical or parse
+    /// table error which stopped the parser.
+    pub fn parse<I>(&self, mut iterator: I) -> Result<ParseForest<'a>, Error>
+    where
+        I: Iterator<Item = LocatedToken<'a>>,
+    {
+        let parse_table = self.parse_table;
+        let mut stack = vec![StackNode {
+            state: 0,
+            level: 0,
+            edges: Vec::new(),
+        }];
+        let mut forest = ForestBuilder {
+            nodes: Vec::new(),
+            non_terminals: BTreeMap::new(),
+        };
+        let mut frontier = vec![0];
+        let mut level = 0;
+        let mut located_token = next_token(&mut iterator)?;
+
+        loop {
+            let kind = &located_token.token.kind;
+            let actions = |state: usize| {
+                if kind.is_eof() {
+                    parse_table.states[state].eof.as_slice()
+                } else {
+                    parse_table.states[state]
+                        .lookahead
+                        .get(kind)
+                        .map_or(&[][..], Vec::as_slice)
+                }
+            };
+
+            // Reduce on every stack until no stack node or edge is added. Reductions along
+            // known paths only add derivations to the shared forest nodes.
+            loop {
+                let mut changed = false;
+                let mut ix = 0;
+                while ix < frontier.len() {
+                    let node = frontier[ix];
+                    for action in actions(stack[node].state) {
+                        let &LRAction::Reduce(non_terminal, rhs) = action else {
+                            continue;
+                        };
+                        for (bottom, children) in paths(&stack, node, rhs.syms.len()) {
+                            let start = stack[bottom].level;
+                            let symbol = forest.non_terminal(
+                                non_terminal,
+                                rhs,
+                                start,
+                                level,
+                                located_token.span,
+                                children,
+                            );
+                            let state = stack[bottom].state;
+                            let next_state = *parse_table.states[state]
+                                .goto
+                                .get(non_terminal)
+                                .ok_or_else(|| {
+                                    Error::Table(format!(
+                                        "State {state} has no goto on {non_terminal}"
+                                    ))
+                                })?;
+                            changed |=
+                                push(&mut stack, &mut frontier, next_state, level, bottom, symbol);
+                        }
+                    }
+                    ix += 1;
+                }
+                if !changed {
+                    break;
+                }
+            }
+
+            if kind.is_eof() {
+                // The accepting stack has the start symbol on top of the bottom node.
+                let root = frontier
+                    .iter()
+                    .filter(|&&node| actions(stack[node].state).contains(&LRAction::Accept))
+                    .flat_map(|&node| &stack[node].edges)
+                    .find_map(|&(below, symbol)| (below == 0).then_some(symbol));
+                if let Some(root) = root {
+                    return Ok(ParseForest {
+                        nodes: forest.nodes,
+                        root,
+                    });
+                }
+            }
+
+            // Shift the token on every stack that can.
+            let terminal = forest.nodes.len();
+            let mut next_frontier = Vec::new();
+            for &node in &frontier {
+                for action in actions(stack[node].state) {
+                    if let &LRAction::Shift(next_state) = action {
+                        push(
+                            &mut stack,
+                            &mut next_frontier,
+                            next_state,
+                            level + 1,
+                            node,
+                            terminal,
+                        );
+                    }
+                }
+            }
+            if next_frontier.is_empty() {
+                if located_token.is_unrecognized() {
+                    return Err(located_token.lexical_error());
+                }
+                let mut expected = BTreeSet::new();
+                let mut eof = false;
+                for &node in &frontier {
+                    let state = &parse_table.states[stack[node].state];
+                    expected.extend(
+                        state
+                            .lookahead
+                            .iter()
+                            .filter(|(_, actions)| !actions.is_empty())
+                            .map(|(&terminal, _)| terminal.clone()),
+                    );
+                    eof |= !state.eof.is_empty();
+                }
+                let mut expected: Vec<_> = expected.into_iter().collect();
+                if eof {
+                    expected.push(Terminal::eof());
+                }
+                return Err(Error::Syntax(ParseError::unexpected(
+                    &located_token.token.kind,
+                    &expected,
+                    located_token.span,
+                )));
+            }
+            forest.nodes.push(ForestNode::Terminal {
+                token: located_token.token.kind,
+                text: located_token.token.text,
+                span: located_token.span,
+            });
+            frontier = next_frontier;
+            level += 1;
+            located_token = next_token(&mut iterator)?;
+        }
+    }
+}
+
+/// Find the paths of the given length down from a stack node, with the node at their bottom
+/// and the parse forest nodes along them.
+fn paths(stack: &[StackNode], node: usize, length: usize) -> Vec<(usize, Vec<usize>)> {
+    if length == 0 {
+        return vec![(node, Vec::new())];
+    }
+    let mut paths = Vec::new();
+    for &(below, symbol) in &stack[node].edges {
+        for (bottom, mut children) in self::paths(stack, below, length - 1) {
+            children.push(symbol);
+            paths.push((bottom, children));
+        }
+    }
+    paths
+}
+
+/// Push a state on top of a stack node, merging it with the frontier node of the same state.
+/// Returns whether a stack node or edge was added.
+fn push(
+    stack: &mut Vec<StackNode>,
+    frontier: &mut Vec<usize>,
+    state: usize,
+    level: usize,
+    below: usize,
+    symbol: usize,
+) -> bool {
+    if let Some(&node) = frontier.iter().find(|&&node| stack[node].state == state) {
+        if stack[node].edges.contains(&(below, symbol)) {
+            return false;
+        }
+        stack[node].edges.push((below, symbol));
+    } else {
+        frontier.push(stack.len());
+        stack.push(StackNode {
+            state,
+            level,
+            edges: vec![(below, symbol)],
+        });
+    }
+    true
+}
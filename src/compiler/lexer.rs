@@ -1,19 +1,39 @@
 use std::iter;
+use std::sync::Arc;
 
-use relex::{Recognizer, RecognizerBuilder, Rule, Token};
+use relex::{Recognizer, RecognizerBuilder, Rule, Token, TokenKind};
 
+use crate::common::line_index::{ColumnUnit, LineIndex};
 use crate::common::parse_tree::Span;
 use crate::common::symbol_table::Terminal;
 use crate::common::token_rules::TokenRules;
+use crate::error::Error;
 
 /// A token with an attached source `Span` (start/end offsets plus line/column).
+///
+/// The token text is a slice of the input, so it keeps its original case even for
+/// case-insensitive token rules.
 pub struct LocatedToken<'a> {
     pub token: Token<'a, Terminal>,
     pub span: Span,
 }
 
+/// How the lexer handles text no token rule matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LexicalRecovery {
+    /// Yield the text as an `<UNRECOGNIZED>` token, which stops the parsers.
+    #[default]
+    Stop,
+
+    /// Yield the text as a token of the reserved `error` terminal. The parser reports it and
+    /// continues, shifting it where error productions allow `error` and skipping it otherwise.
+    ErrorToken,
+}
+
 pub struct Lexer {
     recognizer: Recognizer<Terminal>,
+    recovery: LexicalRecovery,
+    column_unit: ColumnUnit,
 }
 
 impl Lexer {
@@ -21,76 +41,128 @@ impl Lexer {
     ///
     /// # Panics
     ///
-    /// This function will panic if any of the regex patterns in the token rules fail to compile.
+    /// This function will panic if any of the regex patterns in the token rules, the comment or
+    /// the whitespace pattern fail to compile.
     #[must_use]
     pub fn new(token_rules: &TokenRules) -> Self {
         let mut builder = RecognizerBuilder::new();
-        for rule in token_rules {
+        for rule in &token_rules.rules {
             builder = builder.token(
                 Rule::new(rule.kind.clone(), &rule.regex)
                     .expect("Failed to compile regex for lexer rule")
                     .skip(rule.skip),
             );
         }
+
+        // Comments and implicit whitespace are skipped after all other rules failed to match.
+        if let Some(comment) = &token_rules.comment {
+            builder = builder.token(
+                Rule::new(Terminal(Arc::from("<COMMENT>")), comment)
+                    .expect("Failed to compile regex for comments")
+                    .skip(true),
+            );
+        }
+        builder = builder.token(
+            Rule::new(
+                Terminal(Arc::from("<WHITESPACE>")),
+                token_rules.whitespace.pattern(),
+            )
+            .expect("Failed to compile regex for whitespace")
+            .skip(true),
+        );
         let recognizer = builder.build();
-        Self { recognizer }
+        Self {
+            recognizer,
+            recovery: LexicalRecovery::default(),
+            column_unit: ColumnUnit::default(),
+        }
     }
 
-    /// Compute (line, column) from byte offset.
-    fn compute_line_col(input: &str, offset: usize) -> (usize, usize) {
-        let end = offset.min(input.len());
-        let prefix = &input[..end];
-
-        // Number of newline characters gives the line index (1-based).
-        let line = prefix.chars().filter(|&c| c == '\n').count() + 1;
-
-        // Column = number of characters after the last newline in prefix + 1
-        let col = prefix.rfind('\n').map_or_else(
-            || prefix.chars().count() + 1,
-            |last_newline_pos| prefix[last_newline_pos + 1..].chars().count() + 1,
-        );
+    /// Sets how the lexer handles text no token rule matches.
+    #[must_use]
+    pub const fn with_recovery(mut self, recovery: LexicalRecovery) -> Self {
+        self.recovery = recovery;
+        self
+    }
 
-        (line, col)
+    /// Sets the unit the columns of token spans are counted in, characters by default.
+    #[must_use]
+    pub const fn with_column_unit(mut self, column_unit: ColumnUnit) -> Self {
+        self.column_unit = column_unit;
+        self
     }
 
     /// Tokenizes the input string and returns an iterator of `LocatedToken`.
     ///
-    /// # Panics
-    ///
-    /// Panics when an unrecognized token is encountered.
+    /// Text no token rule matches is yielded as `<UNRECOGNIZED>` tokens, which the parsers
+    /// return as `Error::Lexical`, or as `error` tokens depending on the `LexicalRecovery`.
     pub fn tokenize(self, input: &str) -> impl Iterator<Item = LocatedToken<'_>> {
-        let base_iter = self.recognizer.into_lexer(input, 0);
-
-        // Map tokens into LocatedToken and panic immediately on UNRECOGNIZED.
-        let mapped = base_iter.map(move |token: Token<'_, Terminal>| {
-            // Compute line/column based on the token start offset.
-            let (line, column) = Self::compute_line_col(input, token.start);
-            let span = Span::new(token.start, token.end, line, column);
-
-            assert!(
-                token.kind.0.as_ref() != "<UNRECOGNIZED>",
-                "Lexical error: unrecognized token {:?} at input:{}:{}.",
-                token.text,
-                span.line,
-                span.column,
-            );
-
-            LocatedToken { token, span }
-        });
+        let recovery = self.recovery;
+        let line_index = LineIndex::new(input).with_column_unit(self.column_unit);
 
         // Append EOF token at the end.
         let eof_token = {
-            // create eof token and span at end of input
             let token = Token::eof(input);
-            let (line, column) = Self::compute_line_col(input, token.start);
-            let start = token.start;
-            let end = token.end;
-            LocatedToken {
-                token,
-                span: Span::new(start, end, line, column),
-            }
+            let span = line_index.span(token.start, token.end);
+            LocatedToken { token, span }
         };
 
+        let base_iter = self.recognizer.into_lexer(input, 0);
+        let mapped = base_iter.map(move |mut token: Token<'_, Terminal>| {
+            if recovery == LexicalRecovery::ErrorToken && token.kind.is_unrecognized() {
+                token.kind = Terminal::error();
+            }
+            let span = line_index.span(token.start, token.end);
+            LocatedToken { token, span }
+        });
+
         mapped.chain(iter::once(eof_token))
     }
+
+    /// Tokenizes the whole input string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Lexical` for the first text no token rule matches.
+    pub fn try_tokenize(self, input: &str) -> Result<Vec<LocatedToken<'_>>, Error> {
+        self.tokenize(input)
+            .map(|located_token| {
+                if located_token.is_unrecognized() {
+                    Err(located_token.lexical_error())
+                } else {
+                    Ok(located_token)
+                }
+            })
+            .collect()
+    }
+}
+
+impl LocatedToken<'_> {
+    /// Whether the token is text no token rule matches, either an `<UNRECOGNIZED>` token or an
+    /// `error` token of the lexer.
+    #[must_use]
+    pub fn is_unrecognized(&self) -> bool {
+        self.token.kind.is_unrecognized() || self.token.kind == Terminal::error()
+    }
+
+    /// The lexical error of an unrecognized token.
+    #[must_use]
+    pub fn lexical_error<E>(&self) -> Error<E> {
+        Error::Lexical {
+            text: self.token.text.to_string(),
+            span: self.span,
+        }
+    }
+}
+
+/// Take the next token of a token stream for a parser, failing on `<UNRECOGNIZED>` tokens and
+/// on a stream ending before its `<EOF>` token.
+pub(crate) fn next_token<'a, E>(
+    iterator: &mut impl Iterator<Item = LocatedToken<'a>>,
+) -> Result<LocatedToken<'a>, Error<E>> {
+    let located_token = iterator.next().ok_or(Error::UnexpectedEndOfInput)?;
+    if located_token.token.kind.is_unrecognized() {
+        return Err(located_token.lexical_error());
+    }
+    Ok(located_token)
 }
@@ -1,2 +1,4 @@
+pub mod glr_parser;
 pub mod lexer;
 pub mod parser;
+pub mod trace;
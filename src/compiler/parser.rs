@@ -1,94 +1,398 @@
-use relex::TokenKind;
+use std::io::Write;
+
+use relex::{Token, TokenKind};
 
 use crate::common::action::Action;
-use crate::common::grammar::{LR1ParseTable, LRAction};
+use crate::common::grammar::{LR1ParseTable, LR1State, LRAction};
+use crate::common::grammar_rules::Production;
+use crate::common::parse_tree::Span;
 use crate::common::symbol_table::{NonTerminal, Terminal};
 use crate::compiler::lexer::LocatedToken;
+use crate::compiler::trace::{TraceEvent, TraceStep};
+use crate::error::Error;
+
+/// The result of pushing a token into the parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PushResult<R, E> {
+    /// The token was consumed, and the parser needs the next one.
+    NeedMore,
+
+    /// The input was accepted without errors.
+    Accepted(R),
+
+    /// The input was accepted after recovering from errors, with every reported error.
+    Recovered { result: R, errors: Vec<Error<E>> },
+
+    /// The input was rejected, with every reported error followed by the error which stopped
+    /// the parser, if any.
+    Error(Vec<Error<E>>),
+}
+
+/// The stacks of a parse in progress.
+struct ParseState<E> {
+    state_stack: Vec<usize>,
+    /// The spans of the symbols shifted onto the states above the bottom one.
+    span_stack: Vec<Span>,
+    /// The number of tokens to shift before reporting syntax errors again.
+    recovering: usize,
+    errors: Vec<Error<E>>,
+}
 
-pub struct Parser<'a, Action> {
-    parse_table: &'a LR1ParseTable<'a, Terminal, NonTerminal, ()>,
-    semantic_action: Action,
+/// What became of a token pushed into the parser.
+enum Step<R> {
+    Consumed,
+    Accepted(R),
+    Aborted,
+}
+
+/// A callback receiving every step of the parser.
+type Trace<'a> = Box<dyn FnMut(&TraceEvent<'_>) + 'a>;
+
+/// An LALR(1) parser borrowing its parse table, which parses any number of inputs one after
+/// another. Tokens only need to live for the call they are passed to, so a long-lived table
+/// can parse independently owned inputs.
+pub struct Parser<'a, A: Action> {
+    parse_table: &'a LR1ParseTable<'a, Terminal, NonTerminal, Production>,
+    semantic_action: A,
+    state: ParseState<A::ParseError>,
+    trace: Option<Trace<'a>>,
+}
+
+impl<E> ParseState<E> {
+    fn new() -> Self {
+        Self {
+            state_stack: vec![0],
+            span_stack: Vec::new(),
+            recovering: 0,
+            errors: Vec::new(),
+        }
+    }
 }
 
 impl<'a, A> Parser<'a, A>
 where
     A: Action,
 {
-    pub const fn new(
-        parse_table: &'a LR1ParseTable<'a, Terminal, NonTerminal, ()>,
+    pub fn new(
+        parse_table: &'a LR1ParseTable<'a, Terminal, NonTerminal, Production>,
         semantic_action: A,
     ) -> Self {
         Self {
             parse_table,
             semantic_action,
+            state: ParseState::new(),
+            trace: None,
         }
     }
 
+    /// Calls a callback with every step of the parser, to debug the parse of an input.
+    #[must_use]
+    pub fn with_trace(mut self, trace: impl FnMut(&TraceEvent<'_>) + 'a) -> Self {
+        self.trace = Some(Box::new(trace));
+        self
+    }
+
+    /// Writes every step of the parser as a line to a writer, like `yydebug`. Failing writes
+    /// are ignored, so they do not affect the parse.
+    #[must_use]
+    pub fn with_trace_writer(self, mut writer: impl Write + 'a) -> Self {
+        self.with_trace(move |event| {
+            let _ = writeln!(writer, "{event}");
+        })
+    }
+
     /// Parses the input token stream using the LALR(1) parser.
     ///
-    /// # Panics
+    /// # Errors
+    ///
+    /// Returns the first error if the input could not be parsed without errors, or
+    /// `Error::UnexpectedEndOfInput` if the input was rejected without recording one.
+    pub fn parse<'t, I>(&mut self, iterator: I) -> Result<A::ParseResult, Error<A::ParseError>>
+    where
+        I: Iterator<Item = LocatedToken<'t>>,
+    {
+        match self.parse_recovering(iterator) {
+            (Some(result), errors) if errors.is_empty() => Ok(result),
+            (_, errors) => match errors.into_iter().next() {
+                Some(error) => Err(error),
+                None => Err(Error::UnexpectedEndOfInput),
+            },
+        }
+    }
+
+    /// Parses the input token stream using the LALR(1) parser, recovering from syntax errors
+    /// like yacc with productions using the `error` terminal.
     ///
-    /// This function will panic if:
-    /// - The input token stream is empty
-    /// - The state stack becomes empty during parsing
-    /// - Next state cannot be found from the parse table
-    /// - Unexpected end of input token stream occurs during parsing
-    /// - Failed to get action for a particular state and token combination
+    /// On a syntax error, states are popped until one can shift `error`, which is shifted
+    /// before parsing continues with the lookahead. Lookaheads that cannot follow are discarded,
+    /// and further errors are only reported after three tokens have been shifted.
     ///
-    /// # Errors
+    /// `error` tokens of a lexer with `LexicalRecovery::ErrorToken` are reported as lexical
+    /// errors, and shifted where the grammar allows `error` or skipped otherwise.
     ///
-    /// Returns an error if the parsing process encounters an unrecoverable parsing error.
-    pub fn parse<I>(&mut self, mut iterator: I) -> Result<A::ParseResult, A::ParseError>
+    /// Returns the result of the accepted input, `None` if it was rejected, with every reported
+    /// syntax error, whether the parser recovered from them or not, followed by the lexical or
+    /// parse table error which stopped the parser, if any.
+    pub fn parse_recovering<'t, I>(
+        &mut self,
+        mut iterator: I,
+    ) -> (Option<A::ParseResult>, Vec<Error<A::ParseError>>)
     where
-        I: Iterator<Item = LocatedToken<'a>>,
+        I: Iterator<Item = LocatedToken<'t>>,
     {
-        let parse_table = &self.parse_table;
-        let mut state_stack = vec![0];
-        let mut located_token = iterator.next().expect("Input token stream is empty");
+        loop {
+            let result = match iterator.next() {
+                Some(located_token) => self.push(located_token),
+                None => self.finish(Err(Error::UnexpectedEndOfInput)),
+            };
+            match result {
+                PushResult::NeedMore => {}
+                PushResult::Accepted(result) => return (Some(result), Vec::new()),
+                PushResult::Recovered { result, errors } => return (Some(result), errors),
+                PushResult::Error(errors) => return (None, errors),
+            }
+        }
+    }
+
+    /// Pushes the next token of the input into the parser, for callers which receive tokens one
+    /// at a time, e.g. from an event loop. The parse ends at the `<EOF>` token, or earlier on an
+    /// error the parser cannot recover from, and the next token starts a new parse.
+    ///
+    /// Errors are handled like by `parse_recovering`. The symbols left on the stack of a
+    /// rejected input are popped with `Action::on_pop`.
+    pub fn push(
+        &mut self,
+        located_token: LocatedToken<'_>,
+    ) -> PushResult<A::ParseResult, A::ParseError> {
+        match self.step(located_token) {
+            Ok(Step::Consumed) => PushResult::NeedMore,
+            Ok(Step::Accepted(result)) => self.finish(Ok(Some(result))),
+            Ok(Step::Aborted) => self.finish(Ok(None)),
+            Err(error) => self.finish(Err(error)),
+        }
+    }
+
+    /// Abandons the parse in progress, if any, and resets the state the action keeps between
+    /// inputs with `Action::on_reset`, so the parser can be reused for an unrelated input.
+    pub fn reset(&mut self) {
+        self.state = ParseState::new();
+        self.semantic_action.on_reset();
+    }
+
+    /// End the parse in progress, with the result of the accepted input, `None` if it was
+    /// rejected, or the error which stopped it.
+    fn finish(
+        &mut self,
+        result: Result<Option<A::ParseResult>, Error<A::ParseError>>,
+    ) -> PushResult<A::ParseResult, A::ParseError> {
+        let mut state = std::mem::replace(&mut self.state, ParseState::new());
+        if !matches!(result, Ok(Some(_))) {
+            for _ in &state.span_stack {
+                self.semantic_action.on_pop();
+            }
+        }
+        match result {
+            Ok(Some(result)) if state.errors.is_empty() => PushResult::Accepted(result),
+            Ok(Some(result)) => PushResult::Recovered {
+                result,
+                errors: state.errors,
+            },
+            Ok(None) => PushResult::Error(state.errors),
+            Err(error) => {
+                state.errors.push(error);
+                PushResult::Error(state.errors)
+            }
+        }
+    }
+
+    /// Run the parser on a token until it is shifted or discarded, or the parse ends.
+    fn step(
+        &mut self,
+        located_token: LocatedToken<'_>,
+    ) -> Result<Step<A::ParseResult>, Error<A::ParseError>> {
+        let parse_table = self.parse_table;
+        let state = &mut self.state;
+        let error = Terminal::error();
+        if located_token.token.kind.is_unrecognized() {
+            return Err(located_token.lexical_error());
+        }
+        if located_token.token.kind == error {
+            state.errors.push(located_token.lexical_error());
+        }
+
+        let trace = &mut self.trace;
+        let mut trace = |state_stack: &[usize], located_token: &LocatedToken<'_>, step| {
+            if let Some(trace) = trace {
+                trace(&TraceEvent {
+                    state_stack,
+                    lookahead: located_token,
+                    step,
+                });
+            }
+        };
 
         loop {
-            let state = *state_stack
-                .last()
-                .expect("State stack is empty during parsing");
+            let top_state = top(&state.state_stack)?;
             let action = if located_token.token.kind.is_eof() {
-                parse_table.states[state].eof.as_ref()
+                parse_table.states[top_state].eof.as_ref()
             } else {
-                parse_table.states[state]
+                parse_table.states[top_state]
                     .lookahead
                     .get(&located_token.token.kind)
             };
 
             match action {
                 Some(LRAction::Reduce(non_terminal, rhs)) => {
-                    for _ in 0..rhs.syms.len() {
-                        state_stack.pop();
-                    }
-                    let state = *state_stack
-                        .last()
-                        .expect("State stack is empty after reduction");
-                    let next_state = parse_table.states[state]
+                    let len = state
+                        .state_stack
+                        .len()
+                        .checked_sub(rhs.syms.len())
+                        .filter(|&len| len > 0)
+                        .ok_or_else(|| {
+                            Error::Table(format!("State stack is too short to reduce {rhs}"))
+                        })?;
+                    let span = match state.span_stack.get(len - 1..) {
+                        Some([first, .., last]) => first.join(*last),
+                        Some([span]) => *span,
+                        _ => located_token.span.at_start(),
+                    };
+                    let top_state = state.state_stack[len - 1];
+                    let next_state = *parse_table.states[top_state]
                         .goto
                         .get(non_terminal)
-                        .expect("Failed to get next state from parse table");
-                    state_stack.push(*next_state);
-                    self.semantic_action.on_reduce(non_terminal, rhs);
+                        .ok_or_else(|| {
+                            Error::Table(format!("State {top_state} has no goto on {non_terminal}"))
+                        })?;
+                    trace(
+                        &state.state_stack,
+                        &located_token,
+                        TraceStep::Reduce {
+                            non_terminal,
+                            rhs,
+                            goto: next_state,
+                        },
+                    );
+                    state.state_stack.truncate(len);
+                    state.span_stack.truncate(len - 1);
+                    state.state_stack.push(next_state);
+                    state.span_stack.push(span);
+                    self.semantic_action.on_reduce(non_terminal, rhs, span);
                 }
                 Some(LRAction::Shift(next_state)) => {
-                    state_stack.push(*next_state);
-                    self.semantic_action.on_shift(located_token.token);
-                    located_token = iterator
-                        .next()
-                        .expect("Unexpected end of input token stream");
+                    trace(
+                        &state.state_stack,
+                        &located_token,
+                        TraceStep::Shift(*next_state),
+                    );
+                    state.state_stack.push(*next_state);
+                    state.span_stack.push(located_token.span);
+                    self.semantic_action
+                        .on_shift(located_token.token, located_token.span);
+                    state.recovering = state.recovering.saturating_sub(1);
+                    return Ok(Step::Consumed);
                 }
                 Some(LRAction::Accept) => {
-                    return Ok(self.semantic_action.on_accept());
+                    trace(&state.state_stack, &located_token, TraceStep::Accept);
+                    return Ok(Step::Accepted(self.semantic_action.on_accept()));
+                }
+                // Skip unrecognized text the grammar does not allow here.
+                None if located_token.token.kind == error => {
+                    trace(&state.state_stack, &located_token, TraceStep::Discard);
+                    return Ok(Step::Consumed);
                 }
-                _ => {
-                    return Err(self
-                        .semantic_action
-                        .on_error(located_token.token, located_token.span));
+                None => {
+                    trace(&state.state_stack, &located_token, TraceStep::Error);
+                    // No token was shifted since the last error, discard the lookahead.
+                    let discard = state.recovering == 3;
+                    if state.recovering == 0 {
+                        let token = copy_token(&located_token.token);
+                        let expected = expected(&parse_table.states[top_state], &error);
+                        state
+                            .errors
+                            .push(Error::Syntax(self.semantic_action.on_error(
+                                token,
+                                located_token.span,
+                                &expected,
+                            )));
+                    } else if discard && located_token.token.kind.is_eof() {
+                        return Ok(Step::Aborted);
+                    }
+                    state.recovering = 3;
+
+                    // Pop states until one can shift the error terminal.
+                    loop {
+                        let top_state = top(&state.state_stack)?;
+                        if let Some(LRAction::Shift(next_state)) =
+                            parse_table.states[top_state].lookahead.get(&error)
+                        {
+                            trace(
+                                &state.state_stack,
+                                &located_token,
+                                TraceStep::ShiftError(*next_state),
+                            );
+                            let span = located_token.span.at_start();
+                            state.state_stack.push(*next_state);
+                            state.span_stack.push(span);
+                            let mut token = copy_token(&located_token.token);
+                            token.kind = error.clone();
+                            token.end = token.start;
+                            token.text = "";
+                            self.semantic_action.on_shift(token, span);
+                            break;
+                        }
+                        if state.state_stack.len() == 1 {
+                            return Ok(Step::Aborted);
+                        }
+                        trace(&state.state_stack, &located_token, TraceStep::Pop);
+                        state.state_stack.pop();
+                        state.span_stack.pop();
+                        self.semantic_action.on_pop();
+                    }
+                    if discard {
+                        trace(&state.state_stack, &located_token, TraceStep::Discard);
+                        return Ok(Step::Consumed);
+                    }
                 }
             }
         }
     }
 }
+
+/// The state on top of the state stack.
+fn top<E>(state_stack: &[usize]) -> Result<usize, Error<E>> {
+    state_stack
+        .last()
+        .copied()
+        .ok_or_else(|| Error::Table("State stack is empty during parsing".to_string()))
+}
+
+/// The terminals a state has an action for, without the error terminal.
+fn expected(
+    state: &LR1State<'_, Terminal, NonTerminal, Production>,
+    error: &Terminal,
+) -> Vec<Terminal> {
+    let mut expected: Vec<Terminal> = state
+        .lookahead
+        .keys()
+        .filter(|&&terminal| terminal != error)
+        .map(|&terminal| terminal.clone())
+        .collect();
+    if state.eof.is_some() {
+        expected.push(Terminal::eof());
+    }
+    expected
+}
+
+/// Copy the kind and the text of a token, without the tokens it skipped.
+fn copy_token<'a>(token: &Token<'a, Terminal>) -> Token<'a, Terminal> {
+    Token {
+        kind: token.kind.clone(),
+        start: token.start,
+        end: token.end,
+        text: token.text,
+        skip: token.skip,
+        next: None,
+        skipped: Vec::new(),
+        captures: None,
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt::{self, Display};
+
+use crate::common::grammar::Rhs;
+use crate::common::grammar_rules::Production;
+use crate::common::symbol_table::{NonTerminal, Terminal};
+use crate::compiler::lexer::LocatedToken;
+
+/// A step of the LR parser, reported to its trace like `yydebug` output.
+pub struct TraceEvent<'e> {
+    /// The state stack before the step, with the current state on top.
+    pub state_stack: &'e [usize],
+
+    /// The lookahead token the step was chosen by.
+    pub lookahead: &'e LocatedToken<'e>,
+
+    pub step: TraceStep<'e>,
+}
+
+/// What the parser does in a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceStep<'e> {
+    /// Shift the lookahead and go to a state.
+    Shift(usize),
+
+    /// Reduce a rule and go to a state by the goto of its non terminal.
+    Reduce {
+        non_terminal: &'e NonTerminal,
+        rhs: &'e Rhs<Terminal, NonTerminal, Production>,
+        goto: usize,
+    },
+
+    /// Accept the input.
+    Accept,
+
+    /// There is no action for the lookahead, whether the syntax error is reported or not.
+    Error,
+
+    /// Error recovery pops the state on top.
+    Pop,
+
+    /// Error recovery shifts the error terminal and goes to a state.
+    ShiftError(usize),
+
+    /// Discard the lookahead.
+    Discard,
+}
+
+impl Display for TraceEvent<'_> {
+    /// Format the event on one line, e.g. `[0 2 5] NUMBER "1" at 1:5: shift, go to state 3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let states: Vec<_> = self.state_stack.iter().map(ToString::to_string).collect();
+        let token = &self.lookahead.token;
+        let span = self.lookahead.span;
+        write!(
+            f,
+            "[{}] {} {:?} at {}:{}: ",
+            states.join(" "),
+            token.kind,
+            token.text,
+            span.line,
+            span.column
+        )?;
+        match self.step {
+            TraceStep::Shift(state) => write!(f, "shift, go to state {state}"),
+            TraceStep::Reduce {
+                non_terminal,
+                rhs,
+                goto,
+            } => write!(f, "reduce {non_terminal} -> {rhs}, go to state {goto}"),
+            TraceStep::Accept => write!(f, "accept"),
+            TraceStep::Error => write!(f, "syntax error"),
+            TraceStep::Pop => write!(f, "pop state"),
+            TraceStep::ShiftError(state) => write!(f, "shift error, go to state {state}"),
+            TraceStep::Discard => write!(f, "discard lookahead"),
+        }
+    }
+}
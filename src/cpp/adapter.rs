@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use relex::{Token, TokenKind};
 
+use crate::common::line_index::LineIndex;
 use crate::common::parse_tree::Span;
 use crate::common::symbol_table::Terminal;
 use crate::compiler::lexer::LocatedToken;
@@ -49,27 +50,10 @@ impl<'a> LexerAdapter<'a> for CppLexerAdapter {
 
         // Append EOF token at the end
         let eof_token = Token::eof(input);
-        let (line, column) = {
-            // Compute line/column for EOF position
-            let end = eof_token.start;
-            let prefix = &input[..end.min(input.len())];
-            let line = prefix.chars().filter(|&c| c == '\n').count() + 1;
-            let col = prefix.rfind('\n').map_or_else(
-                || prefix.chars().count() + 1,
-                |last_newline_pos| prefix[last_newline_pos + 1..].chars().count() + 1,
-            );
-            (line, col)
-        };
-        let start = eof_token.start;
-        let end = eof_token.end;
+        let span = LineIndex::new(input).span(eof_token.start, eof_token.end);
         result.push(LocatedToken {
             token: eof_token,
-            span: Span {
-                start,
-                end,
-                line,
-                column,
-            },
+            span,
         });
 
         result
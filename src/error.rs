@@ -0,0 +1,49 @@
+use std::fmt::{self, Debug, Display};
+
+use crate::common::parse_tree::{ParseError, Span};
+
+/// Error returned by the lexer and the parsers, which never panic on their input.
+///
+/// Syntax errors are built by `Action::on_error`, so they are of the action's error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error<E = ParseError> {
+    /// Input text no token rule matches.
+    Lexical { text: String, span: Span },
+
+    /// A token the grammar does not allow.
+    Syntax(E),
+
+    /// The token stream ended without an `<EOF>` token.
+    UnexpectedEndOfInput,
+
+    /// The parse table is inconsistent, e.g. a state has no goto for a reduced non terminal.
+    Table(String),
+}
+
+impl<E> Error<E> {
+    /// Returns the syntax error, if this is one.
+    #[must_use]
+    pub fn syntax(self) -> Option<E> {
+        match self {
+            Self::Syntax(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<E: Display> Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lexical { text, span } => write!(
+                f,
+                "Lexical Error at input:{}:{}, unrecognized token {text:?}",
+                span.line, span.column
+            ),
+            Self::Syntax(error) => write!(f, "{error}"),
+            Self::UnexpectedEndOfInput => write!(f, "Unexpected end of input token stream"),
+            Self::Table(message) => write!(f, "Parse table error: {message}"),
+        }
+    }
+}
+
+impl<E: Debug + Display> std::error::Error for Error<E> {}
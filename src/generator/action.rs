@@ -1,15 +1,20 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::iter;
 use std::sync::Arc;
 
-use regex::escape;
+use regex::{Regex, escape};
 use relex::Token;
 
 use crate::common::action::Action;
-use crate::common::grammar_rules::{GrammarRules, Rule as GrammarRule};
+use crate::common::grammar_rules::{
+    Associativity, GrammarRules, Precedence, Production, Rule as GrammarRule,
+};
 use crate::common::parse_tree::{ParseError, ParseTreeNode, Span, Symbol};
 use crate::common::symbol_table::{NonTerminal, SymbolTable, Terminal};
-use crate::common::token_rules::{Rule as TokenRule, TokenRules};
+use crate::common::token_rules::{Rule as TokenRule, TokenRules, Whitespace};
+use crate::common::tree_rules::TreeRules;
+use crate::generator::tree_rules::tree_rules;
 
 #[derive(Debug)]
 pub struct GeneratorResult {
@@ -19,6 +24,9 @@ pub struct GeneratorResult {
     /// Token rules containing regex patterns for terminals.
     pub token_rules: TokenRules,
 
+    /// Tree rules for hiding and dropping nodes of the parse tree.
+    pub tree_rules: TreeRules,
+
     /// Parse tree built from the input.
     pub parse_tree: ParseTreeNode,
 }
@@ -31,12 +39,18 @@ pub struct GeneratorAction {
     /// Productions collected during parsing.
     productions: HashMap<String, Vec<Vec<Symbol>>>,
 
+    /// Names of the productions in order of their first definition, which numbers the rules.
+    production_order: Vec<String>,
+
     /// Regex patterns collected for terminals.
     regex_patterns: HashMap<String, String>,
 
     /// Literal patterns collected for terminals.
     literal_patterns: HashMap<String, Vec<String>>,
 
+    /// Directives collected during parsing with their spans, in order of appearance.
+    directives: Vec<(String, Vec<String>, Span)>,
+
     /// Intermediate results for generating the grammar rules.
     rules: HashMap<NonTerminal, Vec<Vec<String>>>,
 
@@ -46,6 +60,12 @@ pub struct GeneratorAction {
     /// Set of non terminal names that appear on the RHS of any production (cannot be start symbol).
     rhs_non_terminals: HashSet<String>,
 
+    /// Set of helper non terminal names synthesized while desugaring EBNF constructs.
+    helper_non_terminals: HashSet<String>,
+
+    /// Set of terminals referenced by anonymous string literals in non terminal rules.
+    anonymous_literals: HashSet<Terminal>,
+
     /// Symbol table containing all terminals and non terminals.
     symbol_table: SymbolTable,
 
@@ -54,6 +74,9 @@ pub struct GeneratorAction {
 
     /// Token rules containing regex patterns for terminals.
     token_rules: TokenRules,
+
+    /// Tree rules for hiding and dropping nodes of the parse tree.
+    tree_rules: TreeRules,
 }
 
 /// Unquote a string, removing surrounding quotes if they are present.
@@ -91,15 +114,62 @@ fn combine_literals(literals: &[String]) -> String {
     }
 }
 
-/// Insert a terminal into the symbol table, ensuring uniqueness by appending a number if necessary.
-fn insert_unique(symbol_table: &mut SymbolTable, base: &str) -> Terminal {
-    let mut name = base.to_string();
-    let mut counter = 0;
-    while symbol_table.get_terminal_id(&name).is_some() {
-        counter += 1;
-        name = format!("{base}{counter}");
+/// Convert a directive value into a regex pattern, accepting both regexes and literals.
+fn to_pattern(value: &str) -> String {
+    if value.starts_with('/') {
+        strip(value).to_string()
+    } else {
+        escape(unquote(value))
+    }
+}
+
+/// Check that a skipped pattern compiles and cannot match the empty string, which would stall
+/// the lexer.
+fn check_skipped(pattern: &str, directive: &str) -> Result<(), String> {
+    match Regex::new(&format!("^(?:{pattern})$")) {
+        Err(error) => Err(format!("invalid pattern for @{directive}: {error}")),
+        Ok(regex) if regex.is_match("") => Err(format!(
+            "pattern for @{directive} must not match the empty string: {pattern}"
+        )),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Get the single value of a directive.
+fn single_value<'a>(name: &str, values: &'a [String]) -> Result<&'a str, String> {
+    match values {
+        [value] => Ok(value),
+        _ => Err(format!(
+            "directive @{name} expects a single value, found {}",
+            values.join(", ")
+        )),
+    }
+}
+
+/// Convert the value of a `@whitespace` directive.
+fn to_whitespace(values: &[String]) -> Result<Whitespace, String> {
+    let value = single_value("whitespace", values)?;
+    let whitespace = match unquote(value) {
+        "horizontal" => Whitespace::Horizontal,
+        "linefeed" => Whitespace::Linefeed,
+        "vertical" => Whitespace::Vertical,
+        _ if value.starts_with('/') => Whitespace::Custom(to_pattern(value)),
+        _ => return Err(format!("unknown whitespace preset: {value}")),
+    };
+    check_skipped(whitespace.pattern(), "whitespace")?;
+    Ok(whitespace)
+}
+
+/// Check the value of a `@literalws` directive. The lexer skips implicit whitespace between any
+/// two tokens, so only `both` sides of literals can be followed by it.
+fn check_literal_whitespace(values: &[String]) -> Result<(), String> {
+    match unquote(single_value("literalws", values)?) {
+        "both" => Ok(()),
+        value @ ("left" | "right" | "none") => Err(format!(
+            "@literalws = {value} is not supported, implicit whitespace is skipped around every token"
+        )),
+        value => Err(format!("unknown literal whitespace policy: {value}")),
     }
-    symbol_table.insert_terminal(name)
 }
 
 /// Converts a Symbol vector into a string vector.
@@ -110,16 +180,129 @@ fn symbols_to_strings(symbols: &[Vec<Symbol>]) -> Vec<Vec<String>> {
         .collect()
 }
 
+/// Desugars EBNF groups, optionals and repetitions of a single rule into helper productions.
+struct Desugarer<'a> {
+    /// The LHS of the rule being desugared, used as prefix for helper names.
+    lhs: &'a str,
+
+    /// Names that are already taken by the grammar.
+    reserved: &'a HashSet<String>,
+
+    /// Counter used to generate fresh helper names.
+    counter: usize,
+
+    /// Helper productions synthesized so far.
+    helpers: Vec<(String, Vec<Vec<Symbol>>)>,
+}
+
+impl<'a> Desugarer<'a> {
+    const fn new(lhs: &'a str, reserved: &'a HashSet<String>) -> Self {
+        Self {
+            lhs,
+            reserved,
+            counter: 0,
+            helpers: Vec::new(),
+        }
+    }
+
+    /// Generate a fresh helper name, e.g. `expression_repetition1`.
+    fn fresh_name(&mut self, kind: &str) -> String {
+        loop {
+            self.counter += 1;
+            let name = format!("{}_{kind}{}", self.lhs, self.counter);
+            if !self.reserved.contains(&name) {
+                return name;
+            }
+        }
+    }
+
+    /// Desugar every alternative of a production.
+    fn desugar_alternatives(&mut self, alternatives: Vec<Vec<Symbol>>) -> Vec<Vec<Symbol>> {
+        let mut result: Vec<Vec<Symbol>> = Vec::with_capacity(alternatives.len());
+        for symbols in alternatives {
+            let symbols = self.desugar_symbols(symbols);
+            if !result.contains(&symbols) {
+                result.push(symbols);
+            }
+        }
+        result
+    }
+
+    /// Desugar a sequence of symbols, replacing EBNF constructs by helper non terminals.
+    fn desugar_symbols(&mut self, symbols: Vec<Symbol>) -> Vec<Symbol> {
+        let mut result = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            match symbol {
+                // A group with a single alternative is spliced into the sequence, unless it
+                // overrides its precedence or carries a label, which must stay at the end of a
+                // rule.
+                Symbol::Group(mut alternatives)
+                    if alternatives.len() == 1
+                        && !alternatives[0].iter().any(|symbol| {
+                            matches!(symbol, Symbol::Precedence(_) | Symbol::Label(_))
+                        }) =>
+                {
+                    result.extend(self.desugar_symbols(alternatives.remove(0)));
+                }
+                // group = alternative_1 | ... | alternative_n
+                Symbol::Group(alternatives) => {
+                    let name = self.fresh_name("group");
+                    let alternatives = self.desugar_alternatives(alternatives);
+                    self.helpers.push((name.clone(), alternatives));
+                    result.push(Symbol::Identifier(name));
+                }
+                // optional = alternative_1 | ... | alternative_n | EPSILON
+                Symbol::Optional(alternatives) => {
+                    let name = self.fresh_name("optional");
+                    let mut alternatives = self.desugar_alternatives(alternatives);
+                    if !alternatives.contains(&vec![Symbol::Epsilon]) {
+                        alternatives.push(vec![Symbol::Epsilon]);
+                    }
+                    self.helpers.push((name.clone(), alternatives));
+                    result.push(Symbol::Identifier(name));
+                }
+                // repetition = repetition alternative_1 | ... | repetition alternative_n | EPSILON
+                Symbol::Repetition(alternatives) => {
+                    let name = self.fresh_name("repetition");
+                    let mut alternatives: Vec<Vec<Symbol>> = self
+                        .desugar_alternatives(alternatives)
+                        .into_iter()
+                        .filter(|symbols| symbols != &[Symbol::Epsilon])
+                        .map(|symbols| {
+                            iter::once(Symbol::Identifier(name.clone()))
+                                .chain(symbols)
+                                .collect()
+                        })
+                        .collect();
+                    alternatives.push(vec![Symbol::Epsilon]);
+                    self.helpers.push((name.clone(), alternatives));
+                    result.push(Symbol::Identifier(name));
+                }
+                symbol => result.push(symbol),
+            }
+        }
+
+        // EPSILON only stands for itself in an otherwise empty sequence.
+        result.retain(|symbol| *symbol != Symbol::Epsilon);
+        if result.is_empty() {
+            result.push(Symbol::Epsilon);
+        }
+        result
+    }
+}
+
 impl GeneratorResult {
     #[must_use]
     pub const fn new(
         grammar_rules: GrammarRules,
         token_rules: TokenRules,
+        tree_rules: TreeRules,
         parse_tree: ParseTreeNode,
     ) -> Self {
         Self {
             grammar_rules,
             token_rules,
+            tree_rules,
             parse_tree,
         }
     }
@@ -132,15 +315,46 @@ impl GeneratorAction {
                 entry.get_mut().extend(rhs);
             }
             Entry::Vacant(entry) => {
+                self.production_order.push(entry.key().clone());
                 entry.insert(rhs);
             }
         }
     }
 
-    fn generate_result(&mut self) {
-        // Collect all symbols and classify them.
-        for (lhs, rhs_alternatives) in &self.productions {
-            let mut is_terminal = true;
+    /// Replace EBNF groups, optionals and repetitions by fresh helper non terminals, which
+    /// follow the production they were synthesized for.
+    fn desugar_productions(&mut self) {
+        let mut productions = std::mem::take(&mut self.productions);
+        let reserved: HashSet<String> = productions.keys().cloned().collect();
+        for lhs in std::mem::take(&mut self.production_order) {
+            let rhs_alternatives = productions
+                .remove(&lhs)
+                .expect("Ordered production should have been collected");
+            let mut desugarer = Desugarer::new(&lhs, &reserved);
+            let rhs_alternatives = desugarer.desugar_alternatives(rhs_alternatives);
+            self.add_production(lhs.clone(), rhs_alternatives);
+            for (helper, helper_alternatives) in desugarer.helpers {
+                self.helper_non_terminals.insert(helper.clone());
+                self.add_production(helper, helper_alternatives);
+            }
+        }
+    }
+
+    fn generate_result(&mut self) -> Result<(), ParseError> {
+        // Expand EBNF constructs into plain BNF productions.
+        self.desugar_productions();
+
+        // Collect all symbols and classify them, in order of definition so that the order of
+        // the token rules does not depend on the hashing of the productions.
+        for lhs in &self.production_order {
+            let rhs_alternatives = &self.productions[lhs];
+            assert!(
+                *Terminal::error().0 != **lhs,
+                "The error terminal is reserved and cannot be defined"
+            );
+
+            // Helper non terminals stay non terminals even if they only contain literals.
+            let mut is_terminal = !self.helper_non_terminals.contains(lhs);
 
             // Check if this rule defines a terminal or nonterminal.
             for symbols in rhs_alternatives {
@@ -152,7 +366,7 @@ impl GeneratorAction {
                             let terminal = self.symbol_table.insert_terminal(literal.clone());
 
                             // Add token rule for the literal.
-                            self.token_rules.push(TokenRule {
+                            self.token_rules.rules.push(TokenRule {
                                 kind: terminal,
                                 regex: escape(&literal),
                                 skip: false,
@@ -183,13 +397,24 @@ impl GeneratorAction {
                                 self.rhs_non_terminals.insert(identifier.clone());
                             }
                         }
-                        Symbol::Epsilon => {}
+                        Symbol::Epsilon | Symbol::Precedence(_) | Symbol::Label(_) => {}
+                        Symbol::Group(_) | Symbol::Optional(_) | Symbol::Repetition(_) => {
+                            unreachable!("EBNF constructs are desugared before classification")
+                        }
                     }
                 }
             }
 
             // Build symbol table.
             if is_terminal {
+                assert!(
+                    !rhs_alternatives
+                        .iter()
+                        .flatten()
+                        .any(|symbol| matches!(symbol, Symbol::Label(_))),
+                    "Labels are not allowed for terminals: {lhs}"
+                );
+
                 // Add terminal to the symbol table.
                 let terminal = self.symbol_table.insert_terminal(lhs.clone());
 
@@ -201,13 +426,13 @@ impl GeneratorAction {
                 if regex_pattern.is_some() && literal_pattern.is_some() {
                     panic!("Regex patterns and literal patterns cannot be used together");
                 } else if let Some(regex) = regex_pattern {
-                    self.token_rules.push(TokenRule {
+                    self.token_rules.rules.push(TokenRule {
                         kind: terminal,
                         regex: strip(regex).to_string(),
                         skip: false,
                     });
                 } else if let Some(literals) = literal_pattern {
-                    self.token_rules.push(TokenRule {
+                    self.token_rules.rules.push(TokenRule {
                         kind: terminal,
                         regex: combine_literals(literals),
                         skip: false,
@@ -237,49 +462,97 @@ impl GeneratorAction {
             }
         }
 
+        // The reserved error terminal is only shifted by the parser, so it has no token rule.
+        if self.rhs_non_terminals.contains(&*Terminal::error().0) {
+            self.symbol_table
+                .insert_terminal(Terminal::error().0.to_string());
+        }
+
         // Postprocess token rules: remove EPSILON, deduplicate, and prefer named tokens.
-        self.process_token_rules();
+        self.process_token_rules()?;
+
+        // Build precedences, which may declare terminals only used by `%prec`.
+        self.build_precedences();
 
         // Build grammar rules.
         self.build_grammar_rules();
 
+        // Build tree rules.
+        self.build_tree_rules();
+
         // Determine the start symbol.
         self.determine_start_symbol();
+        Ok(())
     }
 
-    fn process_token_rules(&mut self) {
-        // Add token rules for whitespace and comments.
-        let comment = insert_unique(&mut self.symbol_table, "Comment");
-        let whitespace = insert_unique(&mut self.symbol_table, "Whitespace");
-        self.token_rules.push(TokenRule {
-            kind: comment,
-            regex: r"#.*".to_string(),
-            skip: true,
-        });
-        self.token_rules.push(TokenRule {
-            kind: whitespace,
-            regex: r"\s+".to_string(),
-            skip: true,
-        });
+    /// Apply the lexical directives to the token rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error located at the first directive with an invalid value.
+    fn process_token_rules(&mut self) -> Result<(), ParseError> {
+        // Apply lexical directives, comments default to `#` line comments.
+        self.token_rules.comment = Some(r"#.*".to_string());
+        let mut ignore_case = false;
+        let mut ignore_case_tokens = HashMap::new();
+        for (name, values, span) in &self.directives {
+            let result = match name.as_str() {
+                "comment" => single_value(name, values).and_then(|value| {
+                    let comment = to_pattern(value);
+                    check_skipped(&comment, name)?;
+                    self.token_rules.comment = Some(comment);
+                    Ok(())
+                }),
+                "whitespace" => {
+                    to_whitespace(values).map(|whitespace| self.token_rules.whitespace = whitespace)
+                }
+                "literalws" => check_literal_whitespace(values),
+                // Either a global boolean or a list of token names.
+                "ignorecase" => {
+                    match values.as_slice() {
+                        [value] if unquote(value).eq_ignore_ascii_case("true") => {
+                            ignore_case = true;
+                        }
+                        [value] if unquote(value).eq_ignore_ascii_case("false") => {
+                            ignore_case = false;
+                        }
+                        _ => ignore_case_tokens
+                            .extend(values.iter().map(|value| (unquote(value), *span))),
+                    }
+                    Ok(())
+                }
+                _ => Ok(()),
+            };
+            result.map_err(|message| ParseError {
+                message,
+                span: Some(*span),
+                expected: Vec::new(),
+            })?;
+        }
 
         // Remove EPSILON from token rules if it exists.
         if let Some(epsilon) = self.symbol_table.get_terminal_id("EPSILON") {
-            self.token_rules.retain(|rule| rule.kind != epsilon);
+            self.token_rules.rules.retain(|rule| rule.kind != epsilon);
         }
 
-        // Sort so that named tokens (all uppercase or with underscores) come first.
-        self.token_rules.sort_by_key(|rule| {
+        // Sort so that tokens defined by literals, such as keywords, come before those defined by
+        // regexes, and named tokens (all uppercase or with underscores) come first otherwise. The
+        // sort is stable, so tokens keep the order of their definition within each group.
+        self.token_rules.rules.sort_by_key(|rule| {
             let name = rule.kind.0.as_ref();
-            i32::from(
-                !name
-                    .chars()
-                    .all(|char| char.is_ascii_uppercase() || char == '_'),
+            (
+                i32::from(self.regex_patterns.contains_key(name)),
+                i32::from(
+                    !name
+                        .chars()
+                        .all(|char| char.is_ascii_uppercase() || char == '_'),
+                ),
             )
         });
 
         // Deduplicate by (regex, skip), keeping the first occurrence.
         let mut seen: HashMap<(String, bool), usize> = HashMap::new();
-        self.token_rules.retain(|rule| {
+        self.token_rules.rules.retain(|rule| {
             let key = (rule.regex.clone(), rule.skip);
             if let Entry::Vacant(entry) = seen.entry(key) {
                 entry.insert(1);
@@ -288,21 +561,116 @@ impl GeneratorAction {
                 false
             }
         });
+
+        // Make the token rules selected by `@ignorecase` case-insensitive.
+        for rule in &mut self.token_rules.rules {
+            if ignore_case || ignore_case_tokens.remove(rule.kind.0.as_ref()).is_some() {
+                rule.regex = format!("(?i:{})", rule.regex);
+            }
+        }
+        // Report the unknown tokens at the first directive listing one of them.
+        let mut unknown: Vec<_> = ignore_case_tokens.into_iter().collect();
+        unknown.sort_by_key(|&(name, span)| (span.start, name));
+        match unknown.first() {
+            Some(&(_, span)) => Err(ParseError {
+                message: format!(
+                    "unknown tokens in @ignorecase: {}",
+                    unknown
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                span: Some(span),
+                expected: Vec::new(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Build the precedences of terminals from the `@left`, `@right` and `@nonassoc`
+    /// directives, each directive binding tighter than the ones before it.
+    fn build_precedences(&mut self) {
+        let mut level = 0;
+        for (name, values, _) in &self.directives {
+            let associativity = match name.as_str() {
+                "left" => Associativity::Left,
+                "right" => Associativity::Right,
+                "nonassoc" => Associativity::NonAssoc,
+                _ => continue,
+            };
+            level += 1;
+            for value in values {
+                let value = unquote(value);
+                assert!(
+                    self.symbol_table.get_non_terminal_id(value).is_none(),
+                    "Non terminal in @{name}: {value}"
+                );
+
+                // Undefined names are declared as terminals that can only be used by `%prec`.
+                let terminal = self
+                    .symbol_table
+                    .get_terminal_id(value)
+                    .unwrap_or_else(|| self.symbol_table.insert_terminal(value.to_string()));
+                let precedence = Precedence {
+                    level,
+                    associativity,
+                };
+                assert!(
+                    self.grammar_rules
+                        .precedences
+                        .insert(terminal, precedence)
+                        .is_none(),
+                    "Multiple precedences for {value}"
+                );
+            }
+        }
     }
 
-    /// Build the lalr grammar rules from the intermediate `self.rules` mapping.
+    /// Build the lalr grammar rules from the intermediate `self.rules` mapping, in order of the
+    /// productions.
     fn build_grammar_rules(&mut self) {
-        for (lhs, rhs_alternatives) in &self.rules {
+        for name in &self.production_order {
+            let Some((lhs, rhs_alternatives)) = self
+                .symbol_table
+                .get_non_terminal_id(name)
+                .and_then(|non_terminal| self.rules.get_key_value(&non_terminal))
+            else {
+                continue;
+            };
             for rhs in rhs_alternatives {
+                // Split off the label and the precedence override, which are always last.
+                let (rhs, label) = match rhs.split_last() {
+                    Some((last, rest)) if last.starts_with(':') => {
+                        (rest, Some(last[1..].to_string()))
+                    }
+                    _ => (rhs.as_slice(), None),
+                };
+                let (rhs, precedence) = match rhs.split_last() {
+                    Some((last, rest)) if last.starts_with("%prec ") => {
+                        let name = unquote(&last["%prec ".len()..]);
+                        let terminal = self
+                            .symbol_table
+                            .get_terminal_id(name)
+                            .unwrap_or_else(|| panic!("Unknown terminal in %prec: {name}"));
+                        (rest, Some(terminal))
+                    }
+                    _ => (rhs, None),
+                };
+
                 let mut lalr_symbols: Vec<crate::common::grammar::Symbol<Terminal, NonTerminal>> =
                     Vec::new();
                 if rhs.len() != 1 || rhs[0] != "EPSILON" {
                     for symbol in rhs {
+                        let is_literal = unquote(symbol) != symbol;
                         let symbol = unquote(symbol);
                         if let Some(non_terminal) = self.symbol_table.get_non_terminal_id(symbol) {
                             lalr_symbols
                                 .push(crate::common::grammar::Symbol::NonTerminal(non_terminal));
                         } else if let Some(terminal) = self.symbol_table.get_terminal_id(symbol) {
+                            if is_literal {
+                                self.anonymous_literals.insert(terminal.clone());
+                            }
                             lalr_symbols.push(crate::common::grammar::Symbol::Terminal(terminal));
                         } else {
                             panic!("Symbol not found in symbol table: {symbol}");
@@ -312,14 +680,78 @@ impl GeneratorAction {
                 self.grammar_rules.rules.push(GrammarRule {
                     non_terminal: lhs.clone(),
                     rhs: lalr_symbols,
+                    precedence,
+                    label,
                 });
             }
         }
     }
 
-    /// Determine and set the start symbol for `self.grammar_rules` by taking the set
-    /// difference between LHS non-terminals and RHS non-terminals.
+    /// Build the tree rules from the `@hide` and `@drop` directives.
+    fn build_tree_rules(&mut self) {
+        // Helper non terminals are anonymous, so they are always hidden.
+        for helper in &self.helper_non_terminals {
+            let non_terminal = self
+                .symbol_table
+                .get_non_terminal_id(helper)
+                .expect("Helper non terminal should exist in the symbol table");
+            self.tree_rules.hide.insert(non_terminal);
+        }
+
+        for (name, values, _) in &self.directives {
+            match name.as_str() {
+                "hide" => {
+                    for value in values {
+                        let non_terminal = self
+                            .symbol_table
+                            .get_non_terminal_id(value)
+                            .unwrap_or_else(|| panic!("Unknown non terminal in @hide: {value}"));
+                        self.tree_rules.hide.insert(non_terminal);
+                    }
+                }
+                "drop" => {
+                    for value in values {
+                        match value.as_str() {
+                            // Implicit whitespace is already skipped by the lexer.
+                            "whitespace" => {}
+                            "strings" => self
+                                .tree_rules
+                                .drop_terminals
+                                .extend(self.anonymous_literals.iter().cloned()),
+                            _ => {
+                                if let Some(non_terminal) =
+                                    self.symbol_table.get_non_terminal_id(value)
+                                {
+                                    self.tree_rules.drop_non_terminals.insert(non_terminal);
+                                } else if let Some(terminal) =
+                                    self.symbol_table.get_terminal_id(value)
+                                {
+                                    self.tree_rules.drop_terminals.insert(terminal);
+                                } else {
+                                    panic!("Unknown symbol in @drop: {value}");
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Determine and set the start symbol for `self.grammar_rules`, either from the `@start`
+    /// directive or by taking the set difference between LHS non-terminals and RHS non-terminals.
     fn determine_start_symbol(&mut self) {
+        if let Some((name, values, _)) = self.directives.iter().find(|(name, ..)| name == "start") {
+            let start_name =
+                single_value(name, values).unwrap_or_else(|message| panic!("{message}"));
+            self.grammar_rules.start_symbol = self
+                .symbol_table
+                .get_non_terminal_id(start_name)
+                .unwrap_or_else(|| panic!("Unknown non terminal in @start: {start_name}"));
+            return;
+        }
+
         let start_symbols: HashSet<String> = self
             .lhs_non_terminals
             .difference(&self.rhs_non_terminals)
@@ -339,71 +771,47 @@ impl GeneratorAction {
                     .expect("Start symbol should exist in the symbol table as a non terminal");
                 self.grammar_rules.start_symbol = start_symbol;
             }
-            _ => panic!("Multiple start symbols found: {start_symbols:?}"),
+            _ => panic!("Multiple start symbols found: {start_symbols:?}, select one with @start"),
         }
     }
 }
 
 impl Action for GeneratorAction {
-    type ParseResult = GeneratorResult;
+    /// The generated rules, or the error of a directive with an invalid value.
+    type ParseResult = Result<GeneratorResult, ParseError>;
     type ParseError = ParseError;
 
     fn on_reduce(
         &mut self,
         non_terminal: &NonTerminal,
-        rhs: &crate::common::grammar::Rhs<Terminal, NonTerminal, ()>,
+        rhs: &crate::common::grammar::Rhs<Terminal, NonTerminal, Production>,
+        span: Span,
     ) {
-        let non_terminal = non_terminal.clone();
-        let grammar = NonTerminal(Arc::from("Grammar"));
-        let atom = NonTerminal(Arc::from("Atom"));
-        let list = NonTerminal(Arc::from("List"));
-        let expression = NonTerminal(Arc::from("Expression"));
-        let term = NonTerminal(Arc::from("Term"));
-        let factor_repetition = NonTerminal(Arc::from("FactorRepetition"));
         let length = rhs.syms.len();
-
-        if non_terminal == grammar || non_terminal == atom {
-            // Remove these non_terminals from the parse tree.
-        } else if non_terminal == list
-            || non_terminal == expression
-            || non_terminal == term
-            || non_terminal == factor_repetition
-        {
-            // Handle repetition flattening.
-            let mut children = Vec::with_capacity(length);
-            for _ in 0..length {
-                if let Some(child) = self.node_stack.pop() {
-                    if child.is_non_terminal(&non_terminal) {
-                        children.extend(child.collect_children().into_iter().rev());
-                    } else {
-                        children.push(child);
-                    }
-                }
+        let mut children = Vec::with_capacity(length);
+        for _ in 0..length {
+            if let Some(child) = self.node_stack.pop() {
+                children.push(child);
             }
-            children.reverse();
-            let new_node =
-                ParseTreeNode::non_terminal(non_terminal.clone(), children, Span::new(0, 0, 1, 1));
-            self.node_stack.push(new_node);
-        } else {
-            // Default case: build normal nonterminal node, filtering out empty factor repetitions.
-            let mut children = Vec::with_capacity(rhs.syms.len());
-            for _ in 0..length {
-                if let Some(child) = self.node_stack.pop() {
-                    // Filter out empty factor repetitions.
-                    if !(child.is_non_terminal(&factor_repetition) && child.is_empty()) {
-                        children.push(child);
-                    }
-                }
-            }
-            children.reverse();
-            let new_node =
-                ParseTreeNode::non_terminal(non_terminal.clone(), children, Span::new(0, 0, 1, 1));
-            self.node_stack.push(new_node);
         }
+        children.reverse();
+        let children = tree_rules().shape(children);
+        let new_node = ParseTreeNode::non_terminal(non_terminal.clone(), children, span);
+        self.node_stack.push(new_node);
 
-        // Collect productions from the parse tree.
+        // Collect directives and productions from the parse tree.
+        let directive = NonTerminal(Arc::from("Directive"));
         let rule = NonTerminal(Arc::from("Rule"));
-        if non_terminal == rule {
+        if *non_terminal == directive {
+            let node = self
+                .node_stack
+                .last()
+                .expect("Node stack should not be empty");
+            let (name, values) = node
+                .get_directive()
+                .expect("Failed to get name and values from directive");
+            self.directives.push((name, values, node.span()));
+        } else if *non_terminal == rule {
             let node = self
                 .node_stack
                 .last()
@@ -411,38 +819,47 @@ impl Action for GeneratorAction {
             let children = node.get_children();
 
             // rule = IDENTIFIER "=" expression
-            assert!(children.len() == 3);
             self.add_production(
                 children[0].get_lexeme(),
-                children[2]
-                    .get_terms()
-                    .expect("Failed to get terms from expression"),
+                node.get_terms().expect("Failed to get terms from rule"),
             );
         }
     }
 
-    fn on_shift(&mut self, token: Token<Terminal>) {
-        let new_node =
-            ParseTreeNode::terminal(token.kind, token.text.to_string(), Span::new(0, 0, 1, 1));
+    fn on_shift(&mut self, token: Token<Terminal>, span: Span) {
+        let new_node = ParseTreeNode::terminal(token.kind, token.text.to_string(), span);
         self.node_stack.push(new_node);
     }
 
     fn on_accept(&mut self) -> Self::ParseResult {
-        self.generate_result();
+        self.generate_result()?;
         let grammar = NonTerminal(Arc::from("Grammar"));
         let children = std::mem::take(&mut self.node_stack);
-        let root_node = ParseTreeNode::non_terminal(grammar, children, Span::new(0, 0, 1, 1));
-        GeneratorResult::new(
+        let span = ParseTreeNode::span_of(&children);
+        let children = tree_rules().shape(children);
+        let root_node = ParseTreeNode::non_terminal(grammar, children, span);
+        Ok(GeneratorResult::new(
             std::mem::take(&mut self.grammar_rules),
             std::mem::take(&mut self.token_rules),
+            std::mem::take(&mut self.tree_rules),
             root_node,
-        )
+        ))
     }
 
-    fn on_error(&mut self, token: Token<Terminal>, span: Span) -> Self::ParseError {
-        ParseError {
-            message: format!("Unexpected token: {:?}", token.kind),
-            span: Some(span),
-        }
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        ParseError::unexpected(&token.kind, expected, span)
+    }
+
+    fn on_pop(&mut self) {
+        self.node_stack.pop();
+    }
+
+    fn on_reset(&mut self) {
+        *self = Self::default();
     }
 }
@@ -1,7 +1,8 @@
+use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
 
 use crate::common::grammar::{Rhs, Symbol};
-use crate::common::grammar_rules::{GrammarRules, Rule};
+use crate::common::grammar_rules::{GrammarRules, Production, Rule};
 use crate::common::symbol_table::{NonTerminal, Terminal};
 
 #[allow(clippy::vec_init_then_push)]
@@ -30,6 +31,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
         let regex = Terminal(Arc::from("Regex"));
         let identifier = Terminal(Arc::from("Identifier"));
         let left_identifier = Terminal(Arc::from("LeftIdentifier"));
+        let prec = Terminal(Arc::from("Prec"));
+        let label = Terminal(Arc::from("Label"));
 
         // NonTerminal symbols.
         let grammar = NonTerminal(Arc::from("Grammar"));
@@ -38,6 +41,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
         let list = NonTerminal(Arc::from("List"));
         let rule = NonTerminal(Arc::from("Rule"));
         let expression = NonTerminal(Arc::from("Expression"));
+        let alternative = NonTerminal(Arc::from("Alternative"));
+        let precedence = NonTerminal(Arc::from("Precedence"));
         let term = NonTerminal(Arc::from("Term"));
         let factor = NonTerminal(Arc::from("Factor"));
         let factor_repetition = NonTerminal(Arc::from("FactorRepetition"));
@@ -57,6 +62,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(grammar.clone()),
                 Symbol::NonTerminal(directive.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: grammar.clone(),
@@ -64,10 +71,14 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(grammar.clone()),
                 Symbol::NonTerminal(rule.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: grammar.clone(),
             rhs: vec![],
+            precedence: None,
+            label: None,
         });
 
         // directive = "@" IDENTIFIER "=" value
@@ -79,20 +90,28 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::Terminal(equal.clone()),
                 Symbol::NonTerminal(value.clone()),
             ],
+            precedence: None,
+            label: None,
         });
 
         // value = LITERAL | REGEX | list
         rules.push(Rule {
             non_terminal: value.clone(),
             rhs: vec![Symbol::Terminal(literal.clone())],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: value.clone(),
             rhs: vec![Symbol::Terminal(regex.clone())],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: value,
             rhs: vec![Symbol::NonTerminal(list.clone())],
+            precedence: None,
+            label: None,
         });
 
         // list = IDENTIFIER { "," IDENTIFIER }
@@ -103,10 +122,14 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::Terminal(comma),
                 Symbol::Terminal(identifier.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: list,
             rhs: vec![Symbol::Terminal(identifier.clone())],
+            precedence: None,
+            label: None,
         });
 
         // rule = IDENTIFIER "=" expression
@@ -117,20 +140,76 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::Terminal(equal),
                 Symbol::NonTerminal(expression.clone()),
             ],
+            precedence: None,
+            label: None,
         });
 
-        // expression = term { "|" term }
+        // expression = alternative { "|" alternative }
         rules.push(Rule {
             non_terminal: expression.clone(),
             rhs: vec![
                 Symbol::NonTerminal(expression.clone()),
                 Symbol::Terminal(pipe),
-                Symbol::NonTerminal(term.clone()),
+                Symbol::NonTerminal(alternative.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: expression.clone(),
+            rhs: vec![Symbol::NonTerminal(alternative.clone())],
+            precedence: None,
+            label: None,
+        });
+
+        // alternative = term [ precedence ] [ LABEL ]
+        rules.push(Rule {
+            non_terminal: alternative.clone(),
+            rhs: vec![
+                Symbol::NonTerminal(term.clone()),
+                Symbol::NonTerminal(precedence.clone()),
+                Symbol::Terminal(label.clone()),
+            ],
+            precedence: None,
+            label: None,
+        });
+        rules.push(Rule {
+            non_terminal: alternative.clone(),
+            rhs: vec![
+                Symbol::NonTerminal(term.clone()),
+                Symbol::NonTerminal(precedence.clone()),
+            ],
+            precedence: None,
+            label: None,
+        });
+        rules.push(Rule {
+            non_terminal: alternative.clone(),
+            rhs: vec![Symbol::NonTerminal(term.clone()), Symbol::Terminal(label)],
+            precedence: None,
+            label: None,
+        });
+        rules.push(Rule {
+            non_terminal: alternative,
             rhs: vec![Symbol::NonTerminal(term.clone())],
+            precedence: None,
+            label: None,
+        });
+
+        // precedence = "%prec" (IDENTIFIER | LITERAL)
+        rules.push(Rule {
+            non_terminal: precedence.clone(),
+            rhs: vec![
+                Symbol::Terminal(prec.clone()),
+                Symbol::Terminal(identifier.clone()),
+            ],
+            precedence: None,
+            label: None,
+        });
+        rules.push(Rule {
+            non_terminal: precedence,
+            rhs: vec![Symbol::Terminal(prec), Symbol::Terminal(literal.clone())],
+            precedence: None,
+            label: None,
         });
 
         // term = factor { factor } | EMPTY
@@ -140,14 +219,20 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(term.clone()),
                 Symbol::NonTerminal(factor.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: term.clone(),
             rhs: vec![Symbol::NonTerminal(factor.clone())],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: term,
             rhs: vec![Symbol::Terminal(empty)],
+            precedence: None,
+            label: None,
         });
 
         // factor = { WHITESPACE } atom { WHITESPACE } [ lookahead ]
@@ -159,6 +244,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(factor_repetition.clone()),
                 Symbol::NonTerminal(lookahead.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: factor.clone(),
@@ -167,6 +254,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(atom.clone()),
                 Symbol::NonTerminal(factor_repetition.clone()),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: factor_repetition.clone(),
@@ -174,36 +263,52 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(factor_repetition.clone()),
                 Symbol::Terminal(tilde),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: factor_repetition,
             rhs: vec![],
+            precedence: None,
+            label: None,
         });
 
         // atom = LITERAL | IDENTIFIER ! "=" | REGEX | group | optional | repetition
         rules.push(Rule {
             non_terminal: atom.clone(),
             rhs: vec![Symbol::Terminal(literal)],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: atom.clone(),
             rhs: vec![Symbol::Terminal(identifier)],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: atom.clone(),
             rhs: vec![Symbol::Terminal(regex)],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: atom.clone(),
             rhs: vec![Symbol::NonTerminal(group.clone())],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: atom.clone(),
             rhs: vec![Symbol::NonTerminal(optional.clone())],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: atom,
             rhs: vec![Symbol::NonTerminal(repetition.clone())],
+            precedence: None,
+            label: None,
         });
 
         // group = "(" expression ")"
@@ -214,6 +319,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(expression.clone()),
                 Symbol::Terminal(right_parentheses),
             ],
+            precedence: None,
+            label: None,
         });
 
         // optional = "[" expression "]"
@@ -224,6 +331,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(expression.clone()),
                 Symbol::Terminal(right_bracket),
             ],
+            precedence: None,
+            label: None,
         });
 
         // repetition = "{" expression "}"
@@ -234,6 +343,8 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(expression),
                 Symbol::Terminal(right_brace),
             ],
+            precedence: None,
+            label: None,
         });
 
         // lookahead = (POSITIVE_LOOKAHEAD | NEGATIVE_LOOKAHEAD | POSITIVE_LOOKBEHIND | NEGATIVE_LOOKBEHIND) factor
@@ -243,33 +354,47 @@ pub fn grammar_rules() -> &'static GrammarRules {
                 Symbol::NonTerminal(lookahead_group.clone()),
                 Symbol::NonTerminal(factor),
             ],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: lookahead_group.clone(),
             rhs: vec![Symbol::Terminal(positive_look_ahead)],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: lookahead_group.clone(),
             rhs: vec![Symbol::Terminal(negative_look_ahead)],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: lookahead_group.clone(),
             rhs: vec![Symbol::Terminal(positive_look_behind)],
+            precedence: None,
+            label: None,
         });
         rules.push(Rule {
             non_terminal: lookahead_group,
             rhs: vec![Symbol::Terminal(negative_look_behind)],
+            precedence: None,
+            label: None,
         });
 
         GrammarRules {
             start_symbol: grammar,
             rules,
+            precedences: HashMap::new(),
         }
     })
 }
 
 #[must_use]
-pub fn reduce_on(rhs: &Rhs<Terminal, NonTerminal, ()>, lookahead: Option<&Terminal>) -> bool {
+pub fn reduce_on(
+    rhs: &Rhs<Terminal, NonTerminal, Production>,
+    lookahead: Option<&Terminal>,
+) -> bool {
     // Greedy whitespace consumption.
     !matches!((&rhs.syms[..], lookahead), (
     [
@@ -295,7 +420,7 @@ pub fn reduce_on(rhs: &Rhs<Terminal, NonTerminal, ()>, lookahead: Option<&Termin
 
 #[must_use]
 pub const fn priority_of(
-    _rhs: &Rhs<Terminal, NonTerminal, ()>,
+    _rhs: &Rhs<Terminal, NonTerminal, Production>,
     _lookahead: Option<&Terminal>,
 ) -> i32 {
     0
@@ -3,3 +3,4 @@ pub mod grammar_rules;
 pub mod parse_tree;
 pub mod processor;
 pub mod token_rules;
+pub mod tree_rules;
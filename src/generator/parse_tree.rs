@@ -4,10 +4,11 @@ use crate::common::parse_tree::{ParseTreeNode, Symbol};
 use crate::common::symbol_table::{NonTerminal, Terminal};
 
 impl ParseTreeNode {
-    /// Converts a terminal parse tree node to a Symbol.
+    /// Converts a terminal or an EBNF construct parse tree node to a Symbol.
     ///
     /// # Errors
-    /// Returns an error message if the terminal token is not recognized.
+    /// Returns an error message if the terminal token is not recognized, or if the
+    /// non terminal is not a group, optional or repetition.
     pub fn to_symbol(&self) -> Result<Symbol, String> {
         match self {
             Self::Terminal { token, lexeme, .. } => {
@@ -27,108 +28,148 @@ impl ParseTreeNode {
                     Err(format!("Unexpected terminal token: {token:?}"))
                 }
             }
-            Self::NonTerminal { symbol, .. } => Err(format!(
-                "Cannot convert non-terminal symbol to symbol: {symbol:?}"
-            )),
+            Self::NonTerminal { symbol, .. } => {
+                // group = "(" expression ")", optional = "[" expression "]",
+                // repetition = "{" expression "}"
+                let group = NonTerminal(Arc::from("Group"));
+                let optional = NonTerminal(Arc::from("Optional"));
+                let repetition = NonTerminal(Arc::from("Repetition"));
+                if *symbol == group {
+                    Ok(Symbol::Group(self.get_terms()?))
+                } else if *symbol == optional {
+                    Ok(Symbol::Optional(self.get_terms()?))
+                } else if *symbol == repetition {
+                    Ok(Symbol::Repetition(self.get_terms()?))
+                } else {
+                    Err(format!(
+                        "Cannot convert non-terminal symbol to symbol: {symbol:?}"
+                    ))
+                }
+            }
         }
     }
 
-    /// Gets terms from an expression node in the parse tree.
+    /// Gets the name and values from a directive node in the parse tree.
     ///
     /// # Errors
-    /// Returns an error if the node is not an expression, has no children,
-    /// or has unexpected child types.
-    ///
-    /// # Panics
-    /// Panics if there's an error retrieving factors from child nodes.
-    pub fn get_terms(&self) -> Result<Vec<Vec<Symbol>>, String> {
-        // expression  = term { "|" term }
-        let expression = NonTerminal(Arc::from("Expression"));
-        let term = NonTerminal(Arc::from("Term"));
-        let pipe = Terminal(Arc::from("Pipe"));
+    /// Returns an error if the node is not a directive or has unexpected children.
+    pub fn get_directive(&self) -> Result<(String, Vec<String>), String> {
+        // directive = "@" IDENTIFIER "=" value
+        // value = LITERAL | REGEX | list
+        // list = IDENTIFIER { "," IDENTIFIER }
+        let directive = NonTerminal(Arc::from("Directive"));
+        let comma = Terminal(Arc::from("Comma"));
 
-        // Only collect terms if the node is an expression.
-        if !self.is_non_terminal(&expression) {
-            return Err("Collect terms call on non-expression node".to_string());
+        // Only collect directives if the node is a directive.
+        if !self.is_non_terminal(&directive) {
+            return Err("Collect directive call on non-directive node".to_string());
         }
         let children = self.get_children();
-
-        if children.is_empty() {
-            return Err("Expression has no children".to_string());
+        if children.len() != 4 {
+            return Err(format!("Unexpected children in directive: {children:?}"));
         }
 
-        let mut terminals = Vec::new();
-        for child in children {
-            if child.is_non_terminal(&term) {
-                terminals.push(
-                    child
-                        .get_factors()
-                        .expect("Failed to get factors from term"),
-                );
-            } else if !child.is_terminal(&pipe) {
-                return Err(format!("Unexpected child in expression: {child}"));
+        // The list is hidden, so its items are children of the value.
+        let name = children[1].get_lexeme();
+        let values = children[3]
+            .get_children()
+            .iter()
+            .filter(|item| !item.is_terminal(&comma))
+            .map(Self::get_lexeme)
+            .collect();
+        Ok((name, values))
+    }
+
+    /// Gets terms from a rule, group, optional or repetition node in the parse tree, whose
+    /// expression is hidden.
+    ///
+    /// # Errors
+    /// Returns an error if the node has no alternatives, or an alternative has unexpected
+    /// children.
+    pub fn get_terms(&self) -> Result<Vec<Vec<Symbol>>, String> {
+        // expression  = alternative { "|" alternative }
+        let alternative = NonTerminal(Arc::from("Alternative"));
+
+        let mut terms = Vec::new();
+        for child in self.get_children() {
+            if child.is_non_terminal(&alternative) {
+                terms.push(child.get_alternative()?);
             }
         }
-        Ok(terminals)
+        if terms.is_empty() {
+            return Err(format!("Expression has no alternatives: {self}"));
+        }
+        Ok(terms)
     }
 
-    /// Gets factors from a term node in the parse tree.
+    /// Gets factors from an alternative node in the parse tree, followed by its precedence
+    /// override and its label if present.
     ///
     /// # Errors
-    /// Returns an error if the node is not a term or has unexpected children.
-    ///
-    /// # Panics
-    /// Panics if there's an error retrieving atoms from child nodes.
-    pub fn get_factors(&self) -> Result<Vec<Symbol>, String> {
-        // term  = factor { factor } | EMPTY
-        let term = NonTerminal(Arc::from("Term"));
+    /// Returns an error if the node is not an alternative or has unexpected children.
+    pub fn get_alternative(&self) -> Result<Vec<Symbol>, String> {
+        // alternative = term [ precedence ] [ LABEL ]
+        // term = factor { factor } | EMPTY
+        // precedence = "%prec" (IDENTIFIER | LITERAL)
+        let alternative = NonTerminal(Arc::from("Alternative"));
         let factor = NonTerminal(Arc::from("Factor"));
+        let precedence = NonTerminal(Arc::from("Precedence"));
         let empty = Terminal(Arc::from("Empty"));
+        let label = Terminal(Arc::from("Label"));
 
-        // Only collect factors if the node is a term.
-        if !self.is_non_terminal(&term) {
-            return Err("Collect factors call on non-term node".to_string());
+        // Only collect factors if the node is an alternative.
+        if !self.is_non_terminal(&alternative) {
+            return Err("Collect alternative call on non-alternative node".to_string());
         }
         let children = self.get_children();
+        if children.is_empty() {
+            return Err("Alternative has no children".to_string());
+        }
 
-        if children.len() == 1 && children[0].is_terminal(&empty) {
-            // Child is epsilon.
-            Ok(vec![Symbol::Epsilon])
-        } else if children[0].is_non_terminal(&factor) {
-            // Children are factors.
-            let mut terminals = Vec::new();
-            for child in children {
-                terminals.push(child.get_atom().expect("Failed to get atom from factor"));
+        // The term is hidden, so its factors are children of the alternative.
+        let mut factors = Vec::new();
+        for child in children {
+            if child.is_non_terminal(&factor) {
+                factors.push(child.get_atom()?);
+            } else if child.is_terminal(&empty) {
+                factors.push(Symbol::Epsilon);
+            } else if child.is_non_terminal(&precedence) {
+                let name = child
+                    .get_children()
+                    .get(1)
+                    .ok_or_else(|| format!("Missing terminal in precedence: {child}"))?
+                    .get_lexeme();
+                factors.push(Symbol::Precedence(name));
+            } else if child.is_terminal(&label) {
+                let name = child.get_lexeme();
+                factors.push(Symbol::Label(
+                    name.trim().trim_start_matches(':').to_string(),
+                ));
+            } else {
+                return Err(format!("Unexpected child in alternative: {child}"));
             }
-            Ok(terminals)
-        } else {
-            Err(format!("Unexpected children in term: {children:?}"))
         }
+        Ok(factors)
     }
 
     /// Gets atom from a factor node in the parse tree.
     ///
     /// # Errors
-    /// Returns an error if the node is not a factor or has no children.
+    /// Returns an error if the node is not a factor or has no atom.
     pub fn get_atom(&self) -> Result<Symbol, String> {
         // factor = { WHITESPACE } atom { WHITESPACE } [ lookahead ]
         let factor = NonTerminal(Arc::from("Factor"));
-        let factor_repetition = NonTerminal(Arc::from("FactorRepetition"));
+        let tilde = Terminal(Arc::from("Tilde"));
 
         // Only collect atoms if the node is a factor.
         if !self.is_non_terminal(&factor) {
             return Err("Collect atom call on non-factor node".to_string());
         }
-        let children = self.get_children();
-        if children.is_empty() {
-            return Err("Factor has no children".to_string());
-        }
-        // Skip leading and trailing repetitions.
-        if children[0].is_non_terminal(&factor_repetition) {
-            // Skip leading repetition.
-            children[1].to_symbol()
-        } else {
-            children[0].to_symbol()
-        }
+        // The repetitions and the atom are hidden, so skip the leading whitespace.
+        self.get_children()
+            .iter()
+            .find(|child| !child.is_terminal(&tilde))
+            .ok_or_else(|| "Factor has no atom".to_string())?
+            .to_symbol()
     }
 }
@@ -1,7 +1,7 @@
 use std::sync::{Arc, OnceLock};
 
 use crate::common::symbol_table::Terminal;
-use crate::common::token_rules::{Rule, TokenRules};
+use crate::common::token_rules::{Rule, TokenRules, Whitespace};
 
 static TOKEN_RULES: OnceLock<TokenRules> = OnceLock::new();
 
@@ -27,10 +27,10 @@ pub fn token_rules() -> &'static TokenRules {
         let literal = Terminal(Arc::from("Literal"));
         let regex = Terminal(Arc::from("Regex"));
         let identifier = Terminal(Arc::from("Identifier"));
-        let comment = Terminal(Arc::from("Comment"));
-        let whitespace = Terminal(Arc::from("Whitespace"));
+        let prec = Terminal(Arc::from("Prec"));
+        let label = Terminal(Arc::from("Label"));
 
-        vec![
+        let rules = vec![
             Rule {
                 kind: at,
                 regex: r"@".to_string(),
@@ -106,6 +106,16 @@ pub fn token_rules() -> &'static TokenRules {
                 regex: r"<-!".to_string(),
                 skip: false,
             },
+            Rule {
+                kind: prec,
+                regex: r"%prec".to_string(),
+                skip: false,
+            },
+            Rule {
+                kind: label,
+                regex: r":[A-Za-z_][A-Za-z_0-9]*".to_string(),
+                skip: false,
+            },
             Rule {
                 kind: empty,
                 regex: r"EPSILON".to_string(),
@@ -126,16 +136,12 @@ pub fn token_rules() -> &'static TokenRules {
                 regex: r"[A-Za-z_][A-Za-z_0-9]*".to_string(),
                 skip: false,
             },
-            Rule {
-                kind: comment,
-                regex: r"#.*".to_string(),
-                skip: true,
-            },
-            Rule {
-                kind: whitespace,
-                regex: r"\s+".to_string(),
-                skip: true,
-            },
-        ]
+        ];
+
+        TokenRules {
+            rules,
+            comment: Some(r"#.*".to_string()),
+            whitespace: Whitespace::Vertical,
+        }
     })
 }
@@ -0,0 +1,31 @@
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+use crate::common::symbol_table::NonTerminal;
+use crate::common::tree_rules::TreeRules;
+
+static TREE_RULES: OnceLock<TreeRules> = OnceLock::new();
+
+/// Tree rules of the EBNF grammar, flattening its repetitions by hiding the left recursive
+/// non terminals.
+pub fn tree_rules() -> &'static TreeRules {
+    TREE_RULES.get_or_init(|| {
+        let hide = [
+            "Grammar",
+            "List",
+            "Expression",
+            "Term",
+            "FactorRepetition",
+            "Atom",
+        ]
+        .into_iter()
+        .map(|name| NonTerminal(Arc::from(name)))
+        .collect();
+
+        TreeRules {
+            hide,
+            drop_terminals: HashSet::new(),
+            drop_non_terminals: HashSet::new(),
+        }
+    })
+}
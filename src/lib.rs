@@ -1,4 +1,5 @@
 pub mod common;
 pub mod compiler;
 pub mod cpp;
+pub mod error;
 pub mod generator;
@@ -2,21 +2,17 @@
 
 use std::sync::Arc;
 
-use crate::common::action::DefaultAction;
-use crate::common::grammar::Symbol;
-use crate::common::grammar_rules::GrammarRules;
-use crate::common::parse_table::ParseTable;
-use crate::common::symbol_table::Terminal;
-use crate::compiler::lexer::Lexer;
-use crate::compiler::parser::Parser;
-use crate::generator::action::GeneratorAction;
-use crate::generator::grammar_rules::{grammar_rules, priority_of, reduce_on};
-use crate::generator::processor::Processor;
-use crate::generator::token_rules::token_rules;
-
-mod common;
-mod compiler;
-mod generator;
+use rustcc::common::action::DefaultAction;
+use rustcc::common::grammar::Symbol;
+use rustcc::common::grammar_rules::GrammarRules;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::symbol_table::Terminal;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::action::GeneratorAction;
+use rustcc::generator::grammar_rules::{grammar_rules, priority_of, reduce_on};
+use rustcc::generator::processor::Processor;
+use rustcc::generator::token_rules::token_rules;
 
 fn main() {
     // Example EBNF input string.
@@ -108,7 +104,8 @@ fn main() {
     let processed = Processor::process(tokens);
     let mut result = parser
         .parse(processed)
-        .expect("Failed to parse EBNF grammar");
+        .expect("Failed to parse EBNF grammar")
+        .expect("Invalid directive in EBNF grammar");
     println!("{}", result.parse_tree);
 
     // Build the lexer and parser based on the result.
@@ -118,7 +115,7 @@ fn main() {
     let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
     let mut parser = Parser::new(
         &parse_table.parse_table,
-        DefaultAction::new(result.grammar_rules.start_symbol),
+        DefaultAction::with_tree_rules(result.grammar_rules.start_symbol, result.tree_rules),
     );
 
     // Test the generated lexer and parser.
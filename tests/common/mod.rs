@@ -0,0 +1,23 @@
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseError;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::action::{GeneratorAction, GeneratorResult};
+use rustcc::generator::grammar_rules::{grammar_rules, priority_of, reduce_on};
+use rustcc::generator::processor::Processor;
+use rustcc::generator::token_rules::token_rules;
+
+/// Generate the grammar rules and token rules for the given grammar.
+pub fn generate(input: &str) -> GeneratorResult {
+    try_generate(input).unwrap()
+}
+
+/// Generate the grammar rules and token rules for the given grammar, returning the error of a
+/// directive with an invalid value.
+pub fn try_generate(input: &str) -> Result<GeneratorResult, ParseError> {
+    let lexer = Lexer::new(token_rules());
+    let parse_table = ParseTable::new(grammar_rules(), reduce_on, priority_of);
+    let mut parser = Parser::new(&parse_table.parse_table, GeneratorAction::default());
+    let tokens = lexer.tokenize(input);
+    parser.parse(Processor::process(tokens)).unwrap()
+}
@@ -36,7 +36,8 @@ fn conflict_detection() {
     let processed = Processor::process(tokens);
     let result = parser
         .parse(processed)
-        .expect("Failed to parse BNF grammar");
+        .expect("Failed to parse BNF grammar")
+        .expect("Invalid directive in BNF grammar");
 
     // Build the lexer and parser based on the result.
     let lexer = Lexer::new(&result.token_rules);
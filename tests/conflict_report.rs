@@ -0,0 +1,188 @@
+mod common;
+
+use rustcc::common::grammar::{LR1Conflict, Symbol};
+use rustcc::common::parse_table::ParseTable;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn conflict_report() {
+    // Dangling else grammar (shift/reduce) with ambiguous values (reduce/reduce).
+    let input = r#"
+        statement = "if" ID "then" statement
+                  | "if" ID "then" statement "else" statement
+                  | value ";"
+        value     = variable | constant
+        variable  = ID
+        constant  = ID
+
+        ID        = /[a-z]+/
+    "#;
+
+    let result = generate(input);
+
+    // Both conflicts are reported at once, ordered by state.
+    let Err(report) = ParseTable::try_new(&result.grammar_rules, reduce_on, priority_of) else {
+        panic!("Grammar should have conflicts");
+    };
+    assert_eq!(report.conflicts.len(), 2);
+    assert!(report.conflicts[0].state_id() < report.conflicts[1].state_id());
+
+    let shift_reduce = report
+        .conflicts
+        .iter()
+        .find(|conflict| matches!(conflict, LR1Conflict::ShiftReduce { .. }))
+        .unwrap();
+    let LR1Conflict::ShiftReduce { token, rule, .. } = shift_reduce else {
+        unreachable!();
+    };
+    assert_eq!(token.unwrap().0.as_ref(), "else");
+    assert_eq!(rule.0.0.as_ref(), "statement");
+    assert_eq!(rule.1.syms.len(), 4);
+
+    // The completed `if` item and the item shifting `else` are involved.
+    let items = shift_reduce.items();
+    assert_eq!(items.len(), 2);
+    assert!(
+        items
+            .iter()
+            .any(|item| item.pos == 4 && item.rhs.syms.len() == 4)
+    );
+    assert!(items.iter().any(|item| {
+        matches!(item.rhs.syms.get(item.pos), Some(Symbol::Terminal(t)) if t.0.as_ref() == "else")
+    }));
+
+    let reduce_reduce = report
+        .conflicts
+        .iter()
+        .find(|conflict| matches!(conflict, LR1Conflict::ReduceReduce { .. }))
+        .unwrap();
+    let LR1Conflict::ReduceReduce { token, r1, r2, .. } = reduce_reduce else {
+        unreachable!();
+    };
+    assert_eq!(token.unwrap().0.as_ref(), ";");
+    let mut rules = [r1.0.0.as_ref(), r2.0.0.as_ref()];
+    rules.sort_unstable();
+    assert_eq!(rules, ["constant", "variable"]);
+    assert_eq!(reduce_reduce.items().len(), 2);
+}
+
+/// Generate the grammar rules for the given grammar and build the conflict report.
+fn report_for(input: &str) -> String {
+    let result = generate(input);
+    let Err(report) = ParseTable::try_new(&result.grammar_rules, reduce_on, priority_of) else {
+        panic!("Grammar should have conflicts");
+    };
+    report
+        .conflicts
+        .iter()
+        .map(|conflict| report.counterexample(conflict).unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[test]
+fn counterexamples() {
+    // Both derivations of the dangling else share the prefix up to the conflict.
+    let counterexamples = report_for(
+        r#"
+        statement = "if" ID "then" statement
+                  | "if" ID "then" statement "else" statement
+                  | value ";"
+        value     = variable | constant
+        variable  = ID
+        constant  = ID
+
+        ID        = /[a-z]+/
+    "#,
+    );
+    let expected = r#"Reduce example: ID • ";"
+Reduce derivation: [statement -> [value -> [constant -> ID •]] ";"]
+Reduce example: ID • ";"
+Reduce derivation: [statement -> [value -> [variable -> ID •]] ";"]
+
+Reduce example: if ID then if ID then statement • else statement
+Reduce derivation: [statement -> if ID then [statement -> if ID then statement •] else statement]
+Shift example: if ID then if ID then statement • else statement
+Shift derivation: [statement -> if ID then [statement -> if ID then statement • else statement]]"#;
+    assert_eq!(counterexamples, expected);
+
+    // Reductions at the end of the input climb to the start symbol through nullable rests.
+    let counterexamples = report_for(
+        r#"
+        program  = NUMBER value [ ";" ]
+        value    = integer | float
+        integer  = NUMBER
+        float    = NUMBER
+
+        NUMBER   = /[0-9]+/
+    "#,
+    );
+    let expected = "\
+Reduce example: NUMBER NUMBER • program_optional1
+Reduce derivation: [<START> -> [program -> NUMBER [value -> [float -> NUMBER •]] program_optional1]]
+Reduce example: NUMBER NUMBER • program_optional1
+Reduce derivation: [<START> -> [program -> NUMBER [value -> [integer -> NUMBER •]] program_optional1]]
+
+Reduce example: NUMBER NUMBER • program_optional1
+Reduce derivation: [program -> NUMBER [value -> [float -> NUMBER •]] program_optional1]
+Reduce example: NUMBER NUMBER • program_optional1
+Reduce derivation: [program -> NUMBER [value -> [integer -> NUMBER •]] program_optional1]";
+    assert_eq!(counterexamples, expected);
+}
+
+#[test]
+fn conflict_messages() {
+    // Literal terminals are quoted in the messages, unlike named terminals.
+    let result = generate(
+        r#"
+        statement = "if" ID "then" statement
+                  | "if" ID "then" statement "else" statement
+                  | value ";"
+        value     = variable | constant
+        variable  = ID
+        constant  = ID
+
+        ID        = /[a-z]+/
+    "#,
+    );
+    let Err(report) = ParseTable::try_new(&result.grammar_rules, reduce_on, priority_of) else {
+        panic!("Grammar should have conflicts");
+    };
+    let messages = report
+        .conflicts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            r#"State 1: reduce/reduce conflict on ";"
+  constant -> ID .
+  variable -> ID ."#,
+            "State 10: shift/reduce conflict on else
+  statement -> if ID then statement .
+  statement -> if ID then statement . else statement",
+        ]
+    );
+    assert!(report.to_string().starts_with(
+        "2 conflicts: 1 shift/reduce, 1 reduce/reduce\n\nState 1: reduce/reduce conflict on \";\"\n"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "State 1: reduce/reduce conflict on \";\"")]
+fn conflict_panic() {
+    let result = generate(
+        r#"
+        statement = value ";"
+        value     = variable | constant
+        variable  = ID
+        constant  = ID
+
+        ID        = /[a-z]+/
+    "#,
+    );
+    ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+}
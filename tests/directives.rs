@@ -0,0 +1,122 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::{ParseTreeNode, Span};
+use rustcc::common::token_rules::Whitespace;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::{generate, try_generate};
+
+#[test]
+fn lexical_directives() {
+    // Line oriented grammar with C++ style comments and significant newlines.
+    let input = r#"
+        @comment    = /\/\/.*/
+        @whitespace = horizontal
+        @literalws  = both
+
+        program   = { statement }
+        statement = VARIABLE "=" NUMBER NEWLINE
+
+        NEWLINE   = /\n/
+        NUMBER    = /[0-9]+/
+        VARIABLE  = /[a-z]+/
+    "#;
+
+    let result = generate(input);
+
+    // The directives are recorded in the token rules.
+    assert_eq!(result.token_rules.comment.as_deref(), Some(r"\/\/.*"));
+    assert_eq!(result.token_rules.whitespace, Whitespace::Horizontal);
+
+    // Build the lexer and parser based on the result.
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+
+    // Comments are skipped, newlines terminate statements.
+    let test_input = "a = 1 // One.\nb\t= 42\n";
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let tokens = Lexer::new(&result.token_rules).tokenize(test_input);
+    let tree = parser.parse(tokens).unwrap();
+    let mut leaves = Vec::new();
+    terminals(&tree, &mut leaves);
+    assert_eq!(
+        leaves,
+        [
+            ("VARIABLE".to_string(), "a".to_string(), 1),
+            ("\"=\"".to_string(), "=".to_string(), 1),
+            ("NUMBER".to_string(), "1".to_string(), 1),
+            ("NEWLINE".to_string(), "\n".to_string(), 1),
+            ("VARIABLE".to_string(), "b".to_string(), 2),
+            ("\"=\"".to_string(), "=".to_string(), 2),
+            ("NUMBER".to_string(), "42".to_string(), 2),
+            ("NEWLINE".to_string(), "\n".to_string(), 2),
+        ]
+    );
+
+    // A missing newline is a syntax error.
+    let test_input = "a = 1 b = 2\n";
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let tokens = Lexer::new(&result.token_rules).tokenize(test_input);
+    assert!(parser.parse(tokens).is_err());
+}
+
+#[test]
+fn invalid_directives() {
+    let error = |directive: &str| {
+        let input = format!("{directive}\nprogram = NAME\nNAME = /[a-z]+/");
+        try_generate(&input).unwrap_err()
+    };
+
+    // Invalid values are reported at their directive instead of panicking.
+    let unknown = error("@whitespace = sideways");
+    assert_eq!(unknown.message, "unknown whitespace preset: sideways");
+    assert_eq!(unknown.span, Some(Span::new(0, 22, 1, 1)));
+    assert_eq!(
+        error("@comment = /a*/").message,
+        "pattern for @comment must not match the empty string: a*"
+    );
+    assert!(
+        error("@comment = /(/")
+            .message
+            .starts_with("invalid pattern for @comment")
+    );
+    assert_eq!(
+        error("@whitespace = horizontal, vertical").message,
+        "directive @whitespace expects a single value, found horizontal, vertical"
+    );
+
+    // The lexer skips whitespace around every token, so literals cannot restrict it.
+    assert_eq!(
+        error("@literalws = right").message,
+        "@literalws = right is not supported, implicit whitespace is skipped around every token"
+    );
+    assert_eq!(
+        error("@literalws = sideways").message,
+        "unknown literal whitespace policy: sideways"
+    );
+}
+
+/// Collect the name, lexeme and line of every terminal of a parse tree in order.
+fn terminals(node: &ParseTreeNode, result: &mut Vec<(String, String, usize)>) {
+    match node {
+        ParseTreeNode::Terminal {
+            token,
+            lexeme,
+            span,
+        } => result.push((token.to_string(), lexeme.clone(), span.line)),
+        ParseTreeNode::NonTerminal { children, .. } => {
+            for child in children {
+                terminals(child, result);
+            }
+        }
+    }
+}
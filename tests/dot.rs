@@ -0,0 +1,82 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn automaton_dot() {
+    let result = generate(
+        r#"
+        sum    = sum "+" NUMBER | NUMBER
+        NUMBER = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+
+    // States are labeled with their kernel items, transitions with their symbol.
+    let expected = r#"digraph automaton {
+  rankdir=LR;
+  node [shape=box, fontname="monospace"];
+  state0 [label="State 0\l<START> -> . sum\l"];
+  state1 [label="State 1\lsum -> NUMBER .\l"];
+  state2 [label="State 2\l<START> -> sum .\lsum -> sum . \"+\" NUMBER\l"];
+  state3 [label="State 3\lsum -> sum \"+\" . NUMBER\l"];
+  state4 [label="State 4\lsum -> sum \"+\" NUMBER .\l"];
+  state0 -> state1 [label="NUMBER"];
+  state0 -> state2 [label="sum"];
+  state2 -> state3 [label="\"+\""];
+  state3 -> state4 [label="NUMBER"];
+}
+"#;
+    assert_eq!(parse_table.dot(), expected);
+
+    // Parse trees are numbered in pre-order, terminals show their token and lexeme.
+    let lexer = Lexer::new(&result.token_rules);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let tree = parser.parse(lexer.tokenize("1 + 2")).unwrap();
+    let expected = r#"digraph parse_tree {
+  node [fontname="monospace"];
+  node0 [label="sum"];
+  node1 [label="sum"];
+  node2 [label="NUMBER\n1", shape=box];
+  node1 -> node2;
+  node0 -> node1;
+  node3 [label="\"+\"\n+", shape=box];
+  node0 -> node3;
+  node4 [label="NUMBER\n2", shape=box];
+  node0 -> node4;
+}
+"#;
+    assert_eq!(tree.dot(), expected);
+}
+
+#[test]
+fn conflict_dot() {
+    let result = generate(
+        r#"
+        statement = "if" ID "then" statement
+                  | "if" ID "then" statement "else" statement
+                  | ID
+        ID        = /[a-z]+/
+    "#,
+    );
+    let Err(report) = ParseTable::try_new(&result.grammar_rules, reduce_on, priority_of) else {
+        panic!("Grammar should have conflicts");
+    };
+
+    // Only the state with the dangling else conflict is highlighted.
+    let dot = report.automaton().dot();
+    assert_eq!(dot.matches("penwidth=2").count(), 1);
+    assert!(dot.contains(
+        r#"  state6 [label="State 6\lstatement -> if ID then statement .\lstatement -> if ID then statement . else statement\l", color=red, fontcolor=red, penwidth=2];"#
+    ));
+    assert!(dot.contains(r#"  state6 -> state7 [label="else"];"#));
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseTreeNode;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+use rustcc::generator::processor::Processor;
+
+use crate::common::generate;
+
+#[test]
+fn ebnf_grammar() {
+    // Arithmetic grammar using EBNF repetition, optional and group constructs.
+    let input = r#"
+        # Arithmetic Grammar.
+        grammar    = { statement }
+        statement  = expression ";"
+        expression = term { (PLUS | MINUS) term }
+        term       = factor { (DIV | MUL) factor }
+        factor     = [ MINUS ] ( NUMBER | VARIABLE | group )
+        group      = "(" expression ")"
+
+        PLUS       = "+"
+        MINUS      = "-"
+        MUL        = "*"
+        DIV        = "/"
+
+        NUMBER     = /(?:0|(?:[1-9]\d*))(?:\.\d+)?/
+        VARIABLE   = /[A-Za-z]/
+    "#;
+
+    let result = generate(input);
+
+    // EBNF constructs are desugared into helper non terminals following their rule, with
+    // left recursive repetitions and empty alternatives for repetitions and optionals.
+    let rules: Vec<_> = result
+        .grammar_rules
+        .rules
+        .iter()
+        .map(|rule| {
+            let rhs: Vec<_> = rule.rhs.iter().map(ToString::to_string).collect();
+            format!("{} = {}", rule.non_terminal, rhs.join(" "))
+        })
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            "grammar = grammar_repetition1",
+            "grammar_repetition1 = grammar_repetition1 statement",
+            "grammar_repetition1 = ",
+            "statement = expression \";\"",
+            "expression = term expression_repetition1",
+            "expression_group2 = PLUS",
+            "expression_group2 = MINUS",
+            "expression_repetition1 = expression_repetition1 expression_group2 term",
+            "expression_repetition1 = ",
+            "term = factor term_repetition1",
+            "term_group2 = DIV",
+            "term_group2 = MUL",
+            "term_repetition1 = term_repetition1 term_group2 factor",
+            "term_repetition1 = ",
+            "factor = factor_optional1 factor_group2",
+            "factor_optional1 = MINUS",
+            "factor_optional1 = ",
+            "factor_group2 = NUMBER",
+            "factor_group2 = VARIABLE",
+            "factor_group2 = group",
+            "group = \"(\" expression \")\"",
+        ]
+    );
+    assert_eq!(result.grammar_rules.start_symbol.0.as_ref(), "grammar");
+
+    // Build the lexer and parser based on the result.
+    let lexer = Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol),
+    );
+
+    // Test the generated lexer and parser.
+    let test_input = r#"
+        a + b * (c - 42) / d;
+        -c - 3 * x + (y / -z);
+    "#;
+    let tokens = lexer.tokenize(test_input);
+    let processed = Processor::process(tokens);
+    let tree = parser.parse(processed).unwrap();
+
+    // Every statement is a repetition of the start rule.
+    assert_eq!(children_of(&tree, "grammar"), [["grammar_repetition1"]]);
+    assert_eq!(children_of(&tree, "statement").len(), 2);
+
+    // The optionals hold the negations, and the groups their alternative, in input order.
+    assert_eq!(
+        children_of(&tree, "factor_optional1"),
+        [
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec!["-"],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec!["-"],
+        ]
+    );
+    let group = |name| -> Vec<_> {
+        children_of(&tree, name)
+            .into_iter()
+            .map(|children| children.concat())
+            .collect()
+    };
+    assert_eq!(
+        group("factor_group2"),
+        [
+            "a", "b", "group", "c", "42", "d", "c", "3", "x", "group", "y", "z"
+        ]
+    );
+    assert_eq!(group("expression_group2"), ["+", "-", "-", "+"]);
+    assert_eq!(group("term_group2"), ["*", "/", "*", "/"]);
+}
+
+/// Collect the children of every node of a non terminal in pre-order, as the lexemes of
+/// terminals and the names of non terminals.
+fn children_of(node: &ParseTreeNode, name: &str) -> Vec<Vec<String>> {
+    let mut result = Vec::new();
+    if let ParseTreeNode::NonTerminal {
+        symbol, children, ..
+    } = node
+    {
+        if symbol.0.as_ref() == name {
+            result.push(
+                children
+                    .iter()
+                    .map(|child| match child {
+                        ParseTreeNode::Terminal { lexeme, .. } => lexeme.clone(),
+                        ParseTreeNode::NonTerminal { symbol, .. } => symbol.to_string(),
+                    })
+                    .collect(),
+            );
+        }
+        for child in children {
+            result.extend(children_of(child, name));
+        }
+    }
+    result
+}
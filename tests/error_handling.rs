@@ -101,7 +101,7 @@ fn error_handing() {
     // Tokenize and parse the input.
     let tokens = lexer.tokenize(input);
     let processed = Processor::process(tokens);
-    let mut result = parser.parse(processed).unwrap();
+    let mut result = parser.parse(processed).unwrap().unwrap();
 
     // Build the lexer and parser based on the result.
     let lexer = Lexer::new(&result.token_rules);
@@ -0,0 +1,150 @@
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use relex::Token;
+use rustcc::common::action::{Action, DefaultAction};
+use rustcc::common::grammar::Rhs;
+use rustcc::common::grammar_rules::Production;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::{ParseError, Span};
+use rustcc::common::symbol_table::{NonTerminal, Terminal};
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::error::Error;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Action recording the reduced statements and the stack of shifted tokens.
+#[derive(Default)]
+struct StatementAction {
+    stack: Vec<String>,
+    statements: Rc<RefCell<Vec<String>>>,
+}
+
+impl Action for StatementAction {
+    type ParseResult = ();
+    type ParseError = ParseError;
+
+    fn on_reduce(
+        &mut self,
+        non_terminal: &NonTerminal,
+        rhs: &Rhs<Terminal, NonTerminal, Production>,
+        _span: Span,
+    ) {
+        let children = self.stack.split_off(self.stack.len() - rhs.syms.len());
+        if non_terminal.0.as_ref() == "statement" {
+            self.statements.borrow_mut().push(children.join(" "));
+        }
+        self.stack.push(non_terminal.to_string());
+    }
+
+    fn on_shift(&mut self, token: Token<Terminal>, _span: Span) {
+        self.stack.push(if token.kind == Terminal::error() {
+            "<error>".to_string()
+        } else {
+            token.text.to_string()
+        });
+    }
+
+    fn on_accept(&mut self) -> Self::ParseResult {}
+
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        ParseError::unexpected(&token.kind, expected, span)
+    }
+
+    fn on_pop(&mut self) {
+        self.stack.pop();
+    }
+}
+
+#[test]
+fn error_recovery() {
+    let result = generate(
+        r#"
+        program   = { statement }
+        statement = ID "=" NUMBER ";" | error ";"
+
+        ID        = /[a-z]+/
+        NUMBER    = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let parse = |input: &str| {
+        let lexer = Lexer::new(&result.token_rules);
+        let action = StatementAction::default();
+        let statements = Rc::clone(&action.statements);
+        let mut parser = Parser::new(&parse_table.parse_table, action);
+        let (result, errors) = parser.parse_recovering(lexer.tokenize(input));
+        let errors: Vec<_> = errors
+            .into_iter()
+            .map(|error| {
+                let error = error.syntax().unwrap();
+                (error.message, error.span.unwrap().start)
+            })
+            .collect();
+        (result, errors, statements.take())
+    };
+
+    // Every erroneous statement is reported and replaced by an error statement, and the input
+    // is accepted.
+    let (result, errors, statements) = parse("a = 1; b = ; c = 3; d 4; e = 5;");
+    assert_eq!(result, Some(()));
+    assert_eq!(
+        errors,
+        [
+            ("expected NUMBER but found \";\"".to_string(), 11),
+            ("expected \"=\" but found NUMBER".to_string(), 22)
+        ]
+    );
+    assert_eq!(
+        statements,
+        ["a = 1 ;", "<error> ;", "c = 3 ;", "<error> ;", "e = 5 ;"]
+    );
+
+    // Tokens are discarded until the statement ends, without reporting them.
+    let (result, errors, statements) = parse("a = = = 1 2; b = 2;");
+    assert_eq!(result, Some(()));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(statements, ["<error> ;", "b = 2 ;"]);
+
+    // The input can end before the parser recovered.
+    let (result, errors, statements) = parse("a = 1; b");
+    assert_eq!(result, None);
+    assert_eq!(errors, [("expected \"=\" but found <EOF>".to_string(), 8)]);
+    assert_eq!(statements, ["a = 1 ;"]);
+}
+
+#[test]
+fn unrecoverable_error() {
+    let result = generate(
+        r#"
+        program   = { statement }
+        statement = ID "=" NUMBER ";"
+
+        ID        = /[a-z]+/
+        NUMBER    = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+
+    // Without error productions, parsing stops at the first error.
+    let lexer = Lexer::new(&result.token_rules);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let (result, errors) = parser.parse_recovering(lexer.tokenize("a = 1; b = ; c = 3;"));
+    assert!(result.is_none());
+    let [Error::Syntax(error)] = errors.as_slice() else {
+        panic!("Expected a single syntax error, got {errors:?}");
+    };
+    assert_eq!(error.span.unwrap().start, 11);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::iter;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::Span;
+use rustcc::compiler::glr_parser::GLRParser;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::error::Error;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn errors() {
+    let result = generate(
+        r#"
+        sum    = sum "+" NUMBER | NUMBER
+        NUMBER = /[0-9]+/
+    "#,
+    );
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+
+    // Unrecognized text is a lexical error instead of a panic.
+    let lexical = Error::Lexical {
+        text: "$".to_string(),
+        span: Span::new(4, 5, 1, 5),
+    };
+    assert_eq!(
+        parser.parse(lexer().tokenize("1 + $ 2")),
+        Err(lexical.clone())
+    );
+    assert_eq!(
+        parser.parse_recovering(lexer().tokenize("1 + $ 2")),
+        (None, vec![lexical.clone()])
+    );
+    assert_eq!(lexer().try_tokenize("1 + $ 2").err(), Some(lexical.clone()));
+    assert_eq!(
+        lexical.to_string(),
+        r#"Lexical Error at input:1:5, unrecognized token "$""#
+    );
+    assert_eq!(lexer().try_tokenize("1 + 2").unwrap().len(), 4);
+
+    // The token stream must end with an end of input token.
+    assert_eq!(
+        parser.parse(lexer().tokenize("1 + 2").take(3)),
+        Err(Error::UnexpectedEndOfInput)
+    );
+    assert_eq!(
+        parser.parse(iter::empty()),
+        Err(Error::UnexpectedEndOfInput)
+    );
+
+    // The GLR parser reports the same errors.
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let parser = GLRParser::new(&parse_table);
+    assert_eq!(
+        parser.parse(lexer().tokenize("1 + $ 2")).err(),
+        Some(lexical)
+    );
+    assert_eq!(
+        parser.parse(lexer().tokenize("1 +").take(2)).err(),
+        Some(Error::UnexpectedEndOfInput)
+    );
+}
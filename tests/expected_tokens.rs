@@ -0,0 +1,127 @@
+mod common;
+
+use std::sync::Arc;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::grammar::LR1Construction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::symbol_table::Terminal;
+use rustcc::compiler::glr_parser::GLRParser;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+fn terminals(names: &[&str]) -> Vec<Terminal> {
+    names
+        .iter()
+        .map(|&name| Terminal(Arc::from(name)))
+        .collect()
+}
+
+#[test]
+fn expected_tokens() {
+    let result = generate(
+        r#"
+        expression = sum
+        sum        = sum "+" atom | atom
+        atom       = IDENTIFIER | "(" sum ")"
+        IDENTIFIER = /[a-z]+/
+    "#,
+    );
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+
+    let error = parser
+        .parse(lexer().tokenize("a + +"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.expected, terminals(&["(", "IDENTIFIER"]));
+    assert_eq!(
+        error.message,
+        r#"expected one of "(", IDENTIFIER but found "+""#
+    );
+    assert_eq!(error.span.unwrap().start, 4);
+
+    // LALR(1) merges the states after an identifier inside and outside parentheses.
+    let error = parser
+        .parse(lexer().tokenize("a b"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.expected, terminals(&[")", "+", "<EOF>"]));
+    assert_eq!(
+        error.message,
+        r#"expected one of ")", "+", <EOF> but found IDENTIFIER"#
+    );
+
+    // Canonical LR(1) keeps them apart, and the input may end after a complete sum.
+    let canonical = ParseTable::with_construction(
+        &result.grammar_rules,
+        LR1Construction::Canonical,
+        reduce_on,
+        priority_of,
+    );
+    let mut canonical_parser = Parser::new(
+        &canonical.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let error = canonical_parser
+        .parse(lexer().tokenize("a b"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.expected, terminals(&["+", "<EOF>"]));
+
+    let error = parser
+        .parse(lexer().tokenize("(a"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.expected, terminals(&[")", "+"]));
+    assert_eq!(error.message, r#"expected one of ")", "+" but found <EOF>"#);
+
+    // The GLR parser expects the terminals of every live stack.
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let parser = GLRParser::new(&parse_table);
+    let error = parser
+        .parse(lexer().tokenize("a + +"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.expected, terminals(&["(", "IDENTIFIER"]));
+}
+
+#[test]
+fn single_expected_token() {
+    let result = generate(
+        r#"
+        assignment = IDENTIFIER "=" IDENTIFIER
+        IDENTIFIER = /[a-z]+/
+    "#,
+    );
+    let lexer = Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+
+    let error = parser
+        .parse(lexer.tokenize("a b"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.expected, terminals(&["="]));
+    assert_eq!(error.message, r#"expected "=" but found IDENTIFIER"#);
+    assert_eq!(
+        error.to_string(),
+        r#"Parse Error at input:1:3, expected "=" but found IDENTIFIER"#
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseTreeNode;
+use rustcc::compiler::glr_parser::GLRParser;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Format a parse tree with brackets around nonterminals, e.g. `[sum [sum 1] + 2]`.
+fn bracket(node: &ParseTreeNode) -> String {
+    match node {
+        ParseTreeNode::Terminal { lexeme, .. } => lexeme.clone(),
+        ParseTreeNode::NonTerminal { children, .. } => {
+            let children: Vec<_> = children.iter().map(bracket).collect();
+            format!("[{}]", children.join(" "))
+        }
+    }
+}
+
+#[test]
+fn dangling_else() {
+    let result = generate(
+        r#"
+        statement = "if" ID "then" statement
+                  | "if" ID "then" statement "else" statement
+                  | ID
+        ID        = /[a-z]+/
+    "#,
+    );
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let lexer = || Lexer::new(&result.token_rules);
+    let parser = GLRParser::new(&parse_table);
+
+    // The else belongs to either if.
+    let forest = parser
+        .parse(lexer().tokenize("if a then if b then c else d"))
+        .unwrap();
+    assert!(forest.is_ambiguous());
+    let mut trees: Vec<_> = forest.trees().iter().map(bracket).collect();
+    trees.sort();
+    assert_eq!(
+        trees,
+        [
+            "[if a then [if b then [c] else [d]]]",
+            "[if a then [if b then [c]] else [d]]",
+        ]
+    );
+
+    // The first derivation drives a semantic action like the LR parser.
+    let tree = forest.replay(&mut DefaultAction::new(
+        result.grammar_rules.start_symbol.clone(),
+    ));
+    assert!(forest.trees().contains(&tree));
+
+    let forest = parser
+        .parse(lexer().tokenize("if a then b else c"))
+        .unwrap();
+    assert!(!forest.is_ambiguous());
+    assert_eq!(forest.trees().len(), 1);
+
+    let error = parser
+        .parse(lexer().tokenize("if a else b"))
+        .unwrap_err()
+        .syntax()
+        .unwrap();
+    assert_eq!(error.span.unwrap().start, 5);
+}
+
+#[test]
+fn shared_derivations() {
+    let result = generate(
+        r#"
+        sum    = sum "+" sum | NUMBER
+        NUMBER = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let lexer = || Lexer::new(&result.token_rules);
+    let parser = GLRParser::new(&parse_table);
+
+    // The number of derivations grows with the Catalan numbers, the forest only quadratically.
+    for (input, count) in [
+        ("1", 1),
+        ("1 + 2", 1),
+        ("1 + 2 + 3", 2),
+        ("1 + 2 + 3 + 4", 5),
+        ("1 + 2 + 3 + 4 + 5", 14),
+    ] {
+        let forest = parser.parse(lexer().tokenize(input)).unwrap();
+        assert_eq!(forest.trees().len(), count, "{input}");
+    }
+    let forest = parser
+        .parse(lexer().tokenize("1 + 2 + 3 + 4 + 5 + 6 + 7 + 8"))
+        .unwrap();
+    assert_eq!(forest.trees().len(), 429);
+    assert!(forest.nodes.len() < 60);
+
+    // Precedence declarations still resolve conflicts.
+    let result = generate(
+        r#"
+        @left  = "+"
+        sum    = sum "+" sum | NUMBER
+        NUMBER = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let lexer = || Lexer::new(&result.token_rules);
+    let forest = GLRParser::new(&parse_table)
+        .parse(lexer().tokenize("1 + 2 + 3"))
+        .unwrap();
+    let trees: Vec<_> = forest.trees().iter().map(bracket).collect();
+    assert_eq!(trees, ["[[[1] + [2]] + [3]]"]);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::grammar::LR1Construction;
+use rustcc::common::grammar_rules::GrammarRules;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Check whether the grammar rules build a parse table without conflicts.
+fn builds(grammar_rules: &GrammarRules, construction: LR1Construction) -> bool {
+    ParseTable::try_with_construction(grammar_rules, construction, reduce_on, priority_of).is_ok()
+}
+
+#[test]
+fn grammar_classes() {
+    for (input, expected) in [
+        // Complete items are alone in their state.
+        (
+            r#"
+            list       = list "," ID | ID
+            ID         = /[a-z]+/
+            "#,
+            Some(LR1Construction::Lr0),
+        ),
+        // Reducing `expression -> term` must not happen on `*`, which cannot follow it.
+        (
+            r#"
+            expression = expression "+" term | term
+            term       = term "*" NUMBER | NUMBER
+            NUMBER     = /[0-9]+/
+            "#,
+            Some(LR1Construction::Slr),
+        ),
+        // `=` follows `right` only after `left`, which SLR(1) does not distinguish.
+        (
+            r#"
+            assignment = left "=" right | right
+            left       = "*" right | ID
+            right      = left
+            ID         = /[a-z]+/
+            "#,
+            Some(LR1Construction::Lalr),
+        ),
+        // LALR(1) merges the states reducing `first` and `second` with different lookaheads.
+        (
+            r#"
+            sentence   = "a" first "c" | "a" second "d" | "b" second "c" | "b" first "d"
+            first      = X
+            second     = X
+            X          = "x"
+            "#,
+            Some(LR1Construction::Canonical),
+        ),
+        // Ambiguous grammars are not LR(1).
+        (
+            r#"
+            expression = expression "+" expression | NUMBER
+            NUMBER     = /[0-9]+/
+            "#,
+            None,
+        ),
+    ] {
+        let grammar_rules = generate(input).grammar_rules;
+        assert_eq!(ParseTable::classify(&grammar_rules), expected, "{input}");
+
+        // Stronger constructions accept every grammar of the weaker ones, minimal LR(1) accepts
+        // the same grammars as canonical LR(1).
+        for construction in [
+            LR1Construction::Lr0,
+            LR1Construction::Slr,
+            LR1Construction::Lalr,
+            LR1Construction::Canonical,
+            LR1Construction::Minimal,
+        ] {
+            let strength = construction.min(LR1Construction::Canonical);
+            let expected = expected.is_some_and(|class| class <= strength);
+            assert_eq!(
+                builds(&grammar_rules, construction),
+                expected,
+                "{construction} {input}"
+            );
+        }
+    }
+}
+
+#[test]
+fn slr_parse_table() {
+    let result = generate(
+        r#"
+        expression = expression "+" term | term
+        term       = term "*" NUMBER | NUMBER
+        NUMBER     = /[0-9]+/
+    "#,
+    );
+
+    // SLR(1) and LALR(1) share the LR(0) states for this grammar.
+    let slr = ParseTable::with_construction(
+        &result.grammar_rules,
+        LR1Construction::Slr,
+        reduce_on,
+        priority_of,
+    );
+    let lalr = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    assert_eq!(slr.parse_table.states.len(), lalr.parse_table.states.len());
+    assert_eq!(slr.report(), lalr.report());
+
+    let Err(report) = ParseTable::try_with_construction(
+        &result.grammar_rules,
+        LR1Construction::Lr0,
+        reduce_on,
+        priority_of,
+    ) else {
+        panic!("Grammar should not be LR(0)");
+    };
+    assert!(report.conflicts.iter().all(|conflict| {
+        conflict
+            .token()
+            .is_some_and(|token| token.0.as_ref() == "*")
+    }));
+
+    let lexer = Lexer::new(&result.token_rules);
+    let mut parser = Parser::new(
+        &slr.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    assert!(parser.parse(lexer.tokenize("1 + 2 * 3")).is_ok());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseTreeNode;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::action::GeneratorResult;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::{generate, try_generate};
+
+/// Parse the input with the generated lexer and parser.
+fn parse(result: &GeneratorResult, input: &str) -> Option<ParseTreeNode> {
+    let lexer = Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    parser.parse(lexer.tokenize(input)).ok()
+}
+
+/// Collect the lexemes of all terminals in the parse tree.
+fn lexemes(node: &ParseTreeNode, result: &mut Vec<String>) {
+    match node {
+        ParseTreeNode::Terminal { lexeme, .. } => result.push(lexeme.clone()),
+        ParseTreeNode::NonTerminal { children, .. } => {
+            for child in children {
+                lexemes(child, result);
+            }
+        }
+    }
+}
+
+#[test]
+fn ignore_case_per_token() {
+    // Only the listed SQL like keywords are case-insensitive.
+    let result = generate(
+        r#"
+        @ignorecase = SELECT, FROM
+
+        query  = SELECT NAME FROM NAME [ WHERE NAME ]
+
+        SELECT = "select"
+        FROM   = "from"
+        WHERE  = "where"
+        NAME   = /[A-Za-z_]+/
+    "#,
+    );
+
+    // Keywords come before the regex they would also match, in order of their definition.
+    let kinds: Vec<_> = result
+        .token_rules
+        .rules
+        .iter()
+        .map(|rule| rule.kind.to_string())
+        .collect();
+    assert_eq!(kinds, ["SELECT", "FROM", "WHERE", "NAME"]);
+
+    // The original lexemes are preserved.
+    let tree = parse(&result, "SeLeCt name FROM Users where id").unwrap();
+    let mut result_lexemes = Vec::new();
+    lexemes(&tree, &mut result_lexemes);
+    assert_eq!(
+        result_lexemes,
+        ["SeLeCt", "name", "FROM", "Users", "where", "id"]
+    );
+
+    // Other keywords remain case-sensitive, `WHERE` is lexed as a name.
+    assert!(parse(&result, "select name from users WHERE id").is_none());
+
+    // Tokens the grammar does not define are reported at their directive.
+    let error = try_generate(
+        r#"
+        @ignorecase = SELECT, FORM
+        query  = SELECT NAME
+        SELECT = "select"
+        NAME   = /[a-z]+/
+    "#,
+    )
+    .unwrap_err();
+    assert_eq!(error.message, "unknown tokens in @ignorecase: FORM");
+    assert_eq!(error.span.map(|span| span.line), Some(2));
+}
+
+#[test]
+fn ignore_case_global() {
+    // All literals and regexes are case-insensitive.
+    let result = generate(
+        r#"
+        @ignorecase = True
+
+        query  = "select" NAME "from" NAME
+
+        NAME   = /[a-z_]+/
+    "#,
+    );
+
+    let tree = parse(&result, "SELECT Name From Users").unwrap();
+    let mut result_lexemes = Vec::new();
+    lexemes(&tree, &mut result_lexemes);
+    assert_eq!(result_lexemes, ["SELECT", "Name", "From", "Users"]);
+}
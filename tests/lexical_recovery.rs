@@ -0,0 +1,76 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::Span;
+use rustcc::compiler::glr_parser::GLRParser;
+use rustcc::compiler::lexer::{Lexer, LexicalRecovery};
+use rustcc::compiler::parser::Parser;
+use rustcc::error::Error;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Describe the errors by their kind and start offset.
+fn describe(errors: &[Error]) -> Vec<(&'static str, usize)> {
+    errors
+        .iter()
+        .map(|error| match error {
+            Error::Lexical { span, .. } => ("lexical", span.start),
+            Error::Syntax(error) => ("syntax", error.span.unwrap().start),
+            error => panic!("Unexpected error {error:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn lexical_recovery() {
+    let result = generate(
+        r#"
+        program   = { statement }
+        statement = ID "=" NUMBER ";" | error ";"
+
+        ID        = /[a-z]+/
+        NUMBER    = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let parser = || {
+        Parser::new(
+            &parse_table.parse_table,
+            DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+        )
+    };
+    let lexer = |recovery| Lexer::new(&result.token_rules).with_recovery(recovery);
+
+    // By default, the first unrecognized text stops the parser.
+    let input = "a = 1; b = $ 2; c = 3 4;";
+    let (_, errors) = parser().parse_recovering(lexer(LexicalRecovery::Stop).tokenize(input));
+    assert_eq!(describe(&errors), [("lexical", 11)]);
+
+    // Error tokens are skipped where the grammar does not allow them, so later errors are
+    // reported in the same run.
+    let (_, errors) = parser().parse_recovering(lexer(LexicalRecovery::ErrorToken).tokenize(input));
+    assert_eq!(describe(&errors), [("lexical", 11), ("syntax", 22)]);
+    assert_eq!(
+        errors[0],
+        Error::Lexical {
+            text: "$".to_string(),
+            span: Span::new(11, 12, 1, 12),
+        }
+    );
+
+    // Error productions shift them, so no syntax error follows.
+    let (_, errors) = parser()
+        .parse_recovering(lexer(LexicalRecovery::ErrorToken).tokenize("a = 1; $$ ; c = 3;"));
+    assert_eq!(describe(&errors), [("lexical", 7)]);
+    assert!(matches!(&errors[0], Error::Lexical { text, .. } if text == "$$"));
+
+    // The GLR parser stops at unrecognized text.
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let parser = GLRParser::new(&parse_table);
+    let error = parser
+        .parse(lexer(LexicalRecovery::ErrorToken).tokenize("a = $;"))
+        .unwrap_err();
+    assert_eq!(describe(&[error]), [("lexical", 4)]);
+}
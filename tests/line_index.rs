@@ -0,0 +1,130 @@
+use rustcc::common::line_index::{ColumnUnit, LineIndex};
+use rustcc::common::parse_tree::Span;
+use rustcc::common::symbol_table::Terminal;
+use rustcc::common::token_rules::{Rule, TokenRules};
+use rustcc::compiler::lexer::Lexer;
+
+#[test]
+fn line_index() {
+    let input = "ab\ncäd\n\nx";
+    let index = LineIndex::new(input);
+    assert_eq!(index.line_count(), 4);
+    assert_eq!(index.line(2), Some("cäd"));
+    assert_eq!(index.line(3), Some(""));
+    assert_eq!(index.line(4), Some("x"));
+    assert_eq!(index.line(0), None);
+    assert_eq!(index.line(5), None);
+
+    // Columns count characters, also after multibyte ones.
+    assert_eq!(index.line_col(0), (1, 1));
+    assert_eq!(index.line_col(2), (1, 3));
+    assert_eq!(index.line_col(3), (2, 1));
+    assert_eq!(index.line_col(6), (2, 3));
+    assert_eq!(index.line_col(7), (2, 4));
+    assert_eq!(index.line_col(8), (3, 1));
+    assert_eq!(index.line_col(9), (4, 1));
+    assert_eq!(index.line_col(100), (4, 2));
+
+    // Every character boundary round trips.
+    for (offset, _) in input.char_indices().chain([(input.len(), ' ')]) {
+        let (line, column) = index.line_col(offset);
+        assert_eq!(index.offset(line, column), Some(offset));
+    }
+    assert_eq!(index.offset(2, 5), None);
+    assert_eq!(index.offset(1, 0), None);
+    assert_eq!(index.offset(5, 1), None);
+
+    assert_eq!(index.span(4, 6), Span::new(4, 6, 2, 2));
+}
+
+#[test]
+fn lexer_spans() {
+    let token_rules = TokenRules {
+        rules: vec![Rule {
+            kind: Terminal("WORD".into()),
+            regex: "[a-zä]+".to_string(),
+            skip: false,
+        }],
+        ..TokenRules::default()
+    };
+    let input = "ab\ncäd  x\n\n  y";
+    let spans: Vec<_> = Lexer::new(&token_rules)
+        .tokenize(input)
+        .map(|located_token| located_token.span)
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            Span::new(0, 2, 1, 1),
+            Span::new(3, 7, 2, 1),
+            Span::new(9, 10, 2, 6),
+            Span::new(14, 15, 4, 3),
+            Span::new(15, 15, 4, 4),
+        ]
+    );
+}
+
+#[test]
+fn column_units() {
+    // "é" is 2 bytes and 1 UTF-16 code unit, "😀" is 4 bytes and 2 UTF-16 code units.
+    let input = "x\né😀 y";
+    let offset = input.find('y').unwrap();
+    let columns: Vec<_> = [ColumnUnit::Byte, ColumnUnit::Char, ColumnUnit::Utf16]
+        .into_iter()
+        .map(|column_unit| {
+            let index = LineIndex::new(input).with_column_unit(column_unit);
+            let (line, column) = index.line_col(offset);
+            assert_eq!(line, 2);
+            assert_eq!(index.offset(line, column), Some(offset));
+            column
+        })
+        .collect();
+    assert_eq!(columns, [8, 4, 5]);
+
+    // Long lines mixing ASCII and wider characters round trip in every unit.
+    let long_line = "a😀é".repeat(1000);
+    for column_unit in [ColumnUnit::Byte, ColumnUnit::Char, ColumnUnit::Utf16] {
+        let index = LineIndex::new(&long_line).with_column_unit(column_unit);
+        for (offset, _) in long_line.char_indices().chain([(long_line.len(), ' ')]) {
+            let (line, column) = index.line_col(offset);
+            assert_eq!(index.offset(line, column), Some(offset));
+        }
+    }
+    let index = LineIndex::new(&long_line).with_column_unit(ColumnUnit::Utf16);
+    assert_eq!(index.line_col(long_line.len()), (1, 4001));
+    assert_eq!(index.offset(1, 4002), None);
+
+    // Columns within a character have no offset.
+    let index = LineIndex::new(input).with_column_unit(ColumnUnit::Utf16);
+    assert_eq!(index.offset(2, 3), None);
+    let index = LineIndex::new(input).with_column_unit(ColumnUnit::Byte);
+    assert_eq!(index.offset(2, 2), None);
+
+    // The lexer counts columns in the configured unit, and spans convert between units.
+    let token_rules = TokenRules {
+        rules: vec![Rule {
+            kind: Terminal("WORD".into()),
+            regex: "[a-zé😀]+".to_string(),
+            skip: false,
+        }],
+        ..TokenRules::default()
+    };
+    let span = |column_unit| {
+        Lexer::new(&token_rules)
+            .with_column_unit(column_unit)
+            .tokenize(input)
+            .nth(2)
+            .unwrap()
+            .span
+    };
+    assert_eq!(span(ColumnUnit::Utf16), Span::new(offset, offset + 1, 2, 5));
+    let index = LineIndex::new(input);
+    assert_eq!(
+        span(ColumnUnit::Char).with_column_unit(&index, ColumnUnit::Utf16),
+        span(ColumnUnit::Utf16)
+    );
+    assert_eq!(
+        span(ColumnUnit::Utf16).with_column_unit(&index, ColumnUnit::Byte),
+        span(ColumnUnit::Byte)
+    );
+}
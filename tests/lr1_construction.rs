@@ -0,0 +1,121 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::grammar::{LR1Conflict, LR1Construction};
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseTreeNode;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::action::GeneratorAction;
+use rustcc::generator::grammar_rules::{grammar_rules, priority_of, reduce_on};
+use rustcc::generator::processor::Processor;
+use rustcc::generator::token_rules::token_rules;
+
+use crate::common::generate;
+
+#[test]
+fn lr1_but_not_lalr1() {
+    // Merging the states after `a x` and `b x` mixes up the lookaheads of `first` and `second`.
+    let result = generate(
+        r#"
+        sentence = "a" first "c" | "a" second "d" | "b" second "c" | "b" first "d"
+        first    = X
+        second   = X
+
+        X        = "x"
+    "#,
+    );
+
+    let Err(report) = ParseTable::try_new(&result.grammar_rules, reduce_on, priority_of) else {
+        panic!("Grammar should not be LALR(1)");
+    };
+    assert_eq!(report.conflicts.len(), 2);
+    assert!(
+        report
+            .conflicts
+            .iter()
+            .all(|conflict| matches!(conflict, LR1Conflict::ReduceReduce { .. }))
+    );
+    let lalr = report.parse_table.states.len();
+
+    let canonical = ParseTable::with_construction(
+        &result.grammar_rules,
+        LR1Construction::Canonical,
+        reduce_on,
+        priority_of,
+    );
+    let minimal = ParseTable::with_construction(
+        &result.grammar_rules,
+        LR1Construction::Minimal,
+        reduce_on,
+        priority_of,
+    );
+    assert_eq!(canonical.parse_table.states.len(), lalr + 1);
+    assert_eq!(minimal.parse_table.states.len(), lalr + 1);
+
+    // Both tables drive the same runtime parser.
+    for parse_table in [&canonical, &minimal] {
+        let parse = |test_input: &str| {
+            let lexer = Lexer::new(&result.token_rules);
+            let mut parser = Parser::new(
+                &parse_table.parse_table,
+                DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+            );
+            parser.parse(lexer.tokenize(test_input)).ok()
+        };
+        for (test_input, expected) in [
+            ("a x c", "first"),
+            ("a x d", "second"),
+            ("b x c", "second"),
+            ("b x d", "first"),
+        ] {
+            let tree = parse(test_input).unwrap();
+            let [_, ParseTreeNode::NonTerminal { symbol, .. }, _] = tree.get_children() else {
+                panic!("Unexpected tree for {test_input}");
+            };
+            assert_eq!(symbol.0.as_ref(), expected, "{test_input}");
+        }
+        assert!(parse("a x").is_none());
+    }
+}
+
+#[test]
+fn minimal_lr1_of_lalr1_grammar() {
+    // The grammar of the generator is LALR(1), so no states need to stay split.
+    let lalr = ParseTable::new(grammar_rules(), reduce_on, priority_of);
+    let canonical = ParseTable::with_construction(
+        grammar_rules(),
+        LR1Construction::Canonical,
+        reduce_on,
+        priority_of,
+    );
+    let minimal = ParseTable::with_construction(
+        grammar_rules(),
+        LR1Construction::Minimal,
+        reduce_on,
+        priority_of,
+    );
+    assert!(canonical.parse_table.states.len() > lalr.parse_table.states.len());
+    assert_eq!(
+        minimal.parse_table.states.len(),
+        lalr.parse_table.states.len()
+    );
+
+    // The minimal table generates the same grammar rules.
+    let input = r#"
+        expression = expression "+" NUMBER | NUMBER
+        NUMBER     = /[0-9]+/
+    "#;
+    let generate_with = |parse_table: &ParseTable| {
+        let lexer = Lexer::new(token_rules());
+        let mut parser = Parser::new(&parse_table.parse_table, GeneratorAction::default());
+        let tokens = lexer.tokenize(input);
+        parser
+            .parse(Processor::process(tokens))
+            .unwrap()
+            .unwrap()
+            .grammar_rules
+    };
+    assert_eq!(generate_with(&minimal), generate_with(&lalr));
+    assert_eq!(generate_with(&canonical), generate_with(&lalr));
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use std::thread;
+
+use relex::Token;
+use rustcc::common::action::{DefaultAction, ValueAction, ValueStack};
+use rustcc::common::grammar::Rhs;
+use rustcc::common::grammar_rules::Production;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::{ParseError, Span};
+use rustcc::common::symbol_table::{NonTerminal, Terminal};
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::{Parser, PushResult};
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+const GRAMMAR: &str = r#"
+    expression = sum
+    sum        = sum "+" NUMBER | NUMBER
+
+    NUMBER     = /[0-9]+/
+"#;
+
+/// Action summing the numbers of all inputs since the last reset.
+#[derive(Default)]
+struct Total {
+    total: u64,
+}
+
+impl ValueAction for Total {
+    type Value = ();
+    type ParseResult = u64;
+    type ParseError = ParseError;
+
+    fn on_reduce(
+        &mut self,
+        _non_terminal: &NonTerminal,
+        _rhs: &Rhs<Terminal, NonTerminal, Production>,
+        _children: &mut [Self::Value],
+        _span: Span,
+    ) -> Self::Value {
+    }
+
+    fn on_shift(&mut self, token: Token<Terminal>, _span: Span) -> Self::Value {
+        self.total += token.text.parse().unwrap_or(0);
+    }
+
+    fn on_accept(&mut self, _value: Self::Value) -> Self::ParseResult {
+        self.total
+    }
+
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        ParseError::unexpected(&token.kind, expected, span)
+    }
+
+    fn on_reset(&mut self) {
+        self.total = 0;
+    }
+}
+
+#[test]
+fn owned_inputs() {
+    let result = generate(GRAMMAR);
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let start_symbol = result.grammar_rules.start_symbol.clone();
+    let mut parser = Parser::new(&parse_table.parse_table, DefaultAction::new(start_symbol));
+
+    // Every input is dropped before the next one, while the parser lives on.
+    for count in 1..=3 {
+        let input = vec!["7"; count].join(" + ");
+        let tree = parser.parse(lexer().tokenize(&input)).unwrap();
+        assert_eq!(tree.span().end, input.len());
+    }
+}
+
+#[test]
+fn concurrent_parsers() {
+    let result = generate(GRAMMAR);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+
+    // Parsers on other threads share the parse table.
+    let totals: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (1..=4u64)
+            .map(|number| {
+                let token_rules = &result.token_rules;
+                let parse_table = &parse_table.parse_table;
+                scope.spawn(move || {
+                    let input = format!("{number} + {number} + {number}");
+                    let mut parser = Parser::new(parse_table, ValueStack::new(Total::default()));
+                    let total = parser.parse(Lexer::new(token_rules).tokenize(&input));
+                    parser.reset();
+                    total.unwrap()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    assert_eq!(totals, [3, 6, 9, 12]);
+}
+
+#[test]
+fn reset() {
+    let result = generate(GRAMMAR);
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(&parse_table.parse_table, ValueStack::new(Total::default()));
+
+    // The action keeps its state between inputs until the parser is reset.
+    assert_eq!(parser.parse(lexer().tokenize("1 + 2")), Ok(3));
+    assert_eq!(parser.parse(lexer().tokenize("3")), Ok(6));
+    parser.reset();
+    assert_eq!(parser.parse(lexer().tokenize("4")), Ok(4));
+
+    // Resetting abandons the parse in progress.
+    parser.reset();
+    let input = "5 + 6 +".to_string();
+    for token in lexer().tokenize(&input).take(3) {
+        assert_eq!(parser.push(token), PushResult::NeedMore);
+    }
+    drop(input);
+    parser.reset();
+    assert_eq!(parser.parse(lexer().tokenize("8 + 9")), Ok(17));
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::grammar_rules::Associativity;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseTreeNode;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Evaluate an expression parse tree.
+fn evaluate(node: &ParseTreeNode) -> i64 {
+    match node.get_children() {
+        [number] => number.get_lexeme().parse().unwrap(),
+        [minus, operand] if minus.get_lexeme() == "-" => -evaluate(operand),
+        [open, expression, _] if open.get_lexeme() == "(" => evaluate(expression),
+        [left, operator, right] => {
+            let (left, right) = (evaluate(left), evaluate(right));
+            match operator.get_lexeme().as_str() {
+                "+" => left + right,
+                "-" => left - right,
+                "*" => left * right,
+                "/" => left / right,
+                "^" => left.pow(u32::try_from(right).unwrap()),
+                "<" => i64::from(left < right),
+                operator => panic!("Unknown operator: {operator}"),
+            }
+        }
+        children => panic!("Unexpected children: {children:?}"),
+    }
+}
+
+#[test]
+fn precedence_and_associativity() {
+    // Ambiguous expression grammar, disambiguated by precedence declarations.
+    let input = r#"
+        @nonassoc  = "<"
+        @left      = PLUS, MINUS
+        @left      = MUL, DIV
+        @right     = POW
+        @right     = UMINUS
+
+        expression = expression "<" expression
+                   | expression PLUS expression
+                   | expression MINUS expression
+                   | expression MUL expression
+                   | expression DIV expression
+                   | expression POW expression
+                   | MINUS expression %prec UMINUS
+                   | "(" expression ")"
+                   | NUMBER
+
+        PLUS       = "+"
+        MINUS      = "-"
+        MUL        = "*"
+        DIV        = "/"
+        POW        = "^"
+
+        NUMBER     = /[0-9]+/
+    "#;
+
+    let result = generate(input);
+
+    // Later directives bind tighter, undefined names only declare a precedence.
+    let precedence_of = |name: &str| {
+        result
+            .grammar_rules
+            .precedences
+            .iter()
+            .find(|(terminal, _)| terminal.0.as_ref() == name)
+            .map(|(_, precedence)| (precedence.level, precedence.associativity))
+            .unwrap()
+    };
+    assert_eq!(precedence_of("<"), (1, Associativity::NonAssoc));
+    assert_eq!(precedence_of("MINUS"), (2, Associativity::Left));
+    assert_eq!(precedence_of("DIV"), (3, Associativity::Left));
+    assert_eq!(precedence_of("POW"), (4, Associativity::Right));
+    assert_eq!(precedence_of("UMINUS"), (5, Associativity::Right));
+    assert!(result.grammar_rules.rules.iter().any(|rule| {
+        rule.precedence
+            .as_ref()
+            .is_some_and(|t| t.0.as_ref() == "UMINUS")
+    }));
+
+    // Build the lexer and parser based on the result.
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let parse = |test_input: &str| {
+        let lexer = Lexer::new(&result.token_rules);
+        let mut parser = Parser::new(
+            &parse_table.parse_table,
+            DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+        );
+        parser.parse(lexer.tokenize(test_input)).ok()
+    };
+
+    for (test_input, expected) in [
+        ("1 + 2 * 3", 7),
+        ("10 - 4 - 3", 3),
+        ("8 / 4 / 2", 1),
+        ("2 ^ 3 ^ 2", 512),
+        ("-2 ^ 2", 4),
+        ("2 * -3 + 1", -5),
+        ("(1 + 2) * 3", 9),
+        ("1 + 2 < 2 * 2", 1),
+    ] {
+        let tree = parse(test_input).unwrap();
+        assert_eq!(evaluate(&tree), expected, "{test_input}");
+    }
+
+    // Non associative operators cannot be chained.
+    assert!(parse("1 < 2 < 3").is_none());
+}
+
+#[test]
+fn precedence_in_group() {
+    // A group overriding its precedence becomes a rule of its own instead of being spliced.
+    let result = generate(
+        r#"
+        @start     = expression
+        @left      = PLUS
+        @right     = UMINUS
+
+        expression = expression PLUS expression
+                   | ( MINUS expression %prec UMINUS )
+                   | NUMBER
+
+        PLUS       = "+"
+        MINUS      = "-"
+        NUMBER     = /[0-9]+/
+    "#,
+    );
+    let rules: Vec<_> = result
+        .grammar_rules
+        .rules
+        .iter()
+        .map(|rule| {
+            let rhs: Vec<_> = rule.rhs.iter().map(ToString::to_string).collect();
+            let precedence = rule.precedence.as_ref().map(ToString::to_string);
+            (rule.non_terminal.to_string(), rhs.join(" "), precedence)
+        })
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            (
+                "expression".to_string(),
+                "expression PLUS expression".to_string(),
+                None
+            ),
+            (
+                "expression".to_string(),
+                "expression_group1".to_string(),
+                None
+            ),
+            ("expression".to_string(), "NUMBER".to_string(), None),
+            (
+                "expression_group1".to_string(),
+                "MINUS expression".to_string(),
+                Some("UMINUS".to_string())
+            ),
+        ]
+    );
+
+    // The negation binds tighter than the addition.
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let lexer = Lexer::new(&result.token_rules);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let tree = parser.parse(lexer.tokenize("-1 + 2")).unwrap();
+    assert_eq!(tree.get_children().len(), 3);
+}
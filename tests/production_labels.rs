@@ -0,0 +1,181 @@
+mod common;
+
+use relex::Token;
+use rustcc::common::action::{DefaultAction, ValueAction, ValueStack};
+use rustcc::common::grammar::{Rhs, Symbol};
+use rustcc::common::grammar_rules::Production;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::{ParseError, Span};
+use rustcc::common::symbol_table::{NonTerminal, Terminal};
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+const GRAMMAR: &str = r#"
+    @left = PLUS, MINUS
+    @left = MUL
+
+    expression = expr
+    expr       = expr PLUS expr :add
+               | expr MINUS expr :sub
+               | expr MUL expr :mul
+               | MINUS expr %prec MUL :neg
+               | "(" expr ")"
+               | NUMBER :number
+
+    PLUS       = "+"
+    MINUS      = "-"
+    MUL        = "*"
+    NUMBER     = /[0-9]+/
+"#;
+
+/// Action evaluating expressions by the labels of the reduced rules.
+struct Evaluator;
+
+impl ValueAction for Evaluator {
+    type Value = i64;
+    type ParseResult = i64;
+    type ParseError = ParseError;
+
+    fn on_reduce(
+        &mut self,
+        _non_terminal: &NonTerminal,
+        rhs: &Rhs<Terminal, NonTerminal, Production>,
+        children: &mut [Self::Value],
+        _span: Span,
+    ) -> Self::Value {
+        match (rhs.act.label.as_deref(), &*children) {
+            (Some("add"), [lhs, _, rhs]) => lhs + rhs,
+            (Some("sub"), [lhs, _, rhs]) => lhs - rhs,
+            (Some("mul"), [lhs, _, rhs]) => lhs * rhs,
+            (Some("neg"), [_, value]) => -value,
+            (None, [_, value, _]) | (Some("number") | None, [value]) => *value,
+            (label, children) => panic!("Unexpected rule {label:?} with {children:?}"),
+        }
+    }
+
+    fn on_shift(&mut self, token: Token<Terminal>, _span: Span) -> Self::Value {
+        token.text.parse().unwrap_or_default()
+    }
+
+    fn on_accept(&mut self, value: Self::Value) -> Self::ParseResult {
+        value
+    }
+
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        ParseError::unexpected(&token.kind, expected, span)
+    }
+}
+
+#[test]
+fn production_labels() {
+    let result = generate(GRAMMAR);
+
+    // Rules are numbered in order of their definition and keep their labels.
+    let rules: Vec<_> = result
+        .grammar_rules
+        .rules
+        .iter()
+        .map(|rule| (rule.non_terminal.to_string(), rule.label.as_deref()))
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            ("expression".to_string(), None),
+            ("expr".to_string(), Some("add")),
+            ("expr".to_string(), Some("sub")),
+            ("expr".to_string(), Some("mul")),
+            ("expr".to_string(), Some("neg")),
+            ("expr".to_string(), None),
+            ("expr".to_string(), Some("number")),
+        ]
+    );
+    assert_eq!(result.grammar_rules, generate(GRAMMAR).grammar_rules);
+
+    // The label follows the precedence override.
+    let neg = &result.grammar_rules.rules[4];
+    assert_eq!(
+        neg.precedence.as_ref().map(ToString::to_string),
+        Some("MUL".to_string())
+    );
+    assert!(matches!(
+        neg.rhs[..],
+        [Symbol::Terminal(_), Symbol::NonTerminal(_)]
+    ));
+
+    // Every right hand side of the parse table carries the production of its rule.
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    for (non_terminal, alternatives) in &parse_table.grammar.rules {
+        for rhs in alternatives {
+            match result.grammar_rules.rules.get(rhs.act.id) {
+                Some(rule) => {
+                    assert_eq!(&rule.non_terminal, non_terminal);
+                    assert_eq!(rule.rhs, rhs.syms);
+                    assert_eq!(rule.label, rhs.act.label);
+                }
+                None => assert_eq!(non_terminal.to_string(), "<START>"),
+            }
+        }
+    }
+
+    // Actions tell the alternatives apart by their labels.
+    let lexer = Lexer::new(&result.token_rules);
+    let mut parser = Parser::new(&parse_table.parse_table, ValueStack::new(Evaluator));
+    let value = parser.parse(lexer.tokenize("1 + 2 * (3 - -4) - 5"));
+    assert_eq!(value, Ok(10));
+}
+
+#[test]
+fn label_in_group() {
+    let result = generate(
+        r#"
+        @start = expr
+        expr   = ( NUM :one ) NUM
+
+        NUM    = /[0-9]+/
+    "#,
+    );
+
+    // A group carrying a label becomes a helper rule with the label.
+    let rules: Vec<_> = result
+        .grammar_rules
+        .rules
+        .iter()
+        .map(|rule| (rule.non_terminal.to_string(), rule.label.as_deref()))
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            ("expr".to_string(), None),
+            ("expr_group1".to_string(), Some("one")),
+        ]
+    );
+
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let lexer = Lexer::new(&result.token_rules);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    assert!(parser.parse(lexer.tokenize("1 2")).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "Labels are not allowed for terminals: ADD")]
+fn label_on_terminal() {
+    generate(
+        r#"
+        expr = expr ADD NUM | NUM
+
+        ADD  = "+" :plus
+        NUM  = /[0-9]+/
+    "#,
+    );
+}
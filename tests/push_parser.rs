@@ -0,0 +1,66 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::{Parser, PushResult};
+use rustcc::error::Error;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn push_parser() {
+    let result = generate(
+        r#"
+        sum    = sum "+" NUMBER | NUMBER
+        NUMBER = /[0-9]+/
+    "#,
+    );
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let action = || DefaultAction::new(result.grammar_rules.start_symbol.clone());
+    let expected = Parser::new(&parse_table.parse_table, action())
+        .parse(lexer().tokenize("1 + 2"))
+        .unwrap();
+
+    // The parser needs tokens until the end of input.
+    let mut parser = Parser::new(&parse_table.parse_table, action());
+    let mut results = Vec::new();
+    for located_token in lexer().tokenize("1 + 2") {
+        results.push(parser.push(located_token));
+    }
+    assert_eq!(
+        results,
+        [
+            PushResult::NeedMore,
+            PushResult::NeedMore,
+            PushResult::NeedMore,
+            PushResult::Accepted(expected.clone()),
+        ]
+    );
+
+    // A rejected input ends the parse, and the next token starts a new one.
+    let mut tokens = lexer().tokenize("1 + + 2");
+    assert_eq!(parser.push(tokens.next().unwrap()), PushResult::NeedMore);
+    assert_eq!(parser.push(tokens.next().unwrap()), PushResult::NeedMore);
+    let PushResult::Error(errors) = parser.push(tokens.next().unwrap()) else {
+        panic!("Expected a syntax error");
+    };
+    assert!(matches!(errors.as_slice(), [Error::Syntax(error)] if error.span.unwrap().start == 4));
+
+    let mut result = PushResult::NeedMore;
+    for located_token in lexer().tokenize("1 + 2") {
+        assert_eq!(result, PushResult::NeedMore);
+        result = parser.push(located_token);
+    }
+    assert_eq!(result, PushResult::Accepted(expected));
+
+    // Unrecognized text stops the parse.
+    let mut tokens = lexer().tokenize("1 $");
+    assert_eq!(parser.push(tokens.next().unwrap()), PushResult::NeedMore);
+    assert!(matches!(
+        parser.push(tokens.next().unwrap()),
+        PushResult::Error(errors) if matches!(errors.as_slice(), [Error::Lexical { .. }])
+    ));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use rustcc::common::parse_table::ParseTable;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn automaton_report() {
+    let result = generate(
+        r#"
+        sum    = sum "+" NUMBER | NUMBER
+        NUMBER = /[0-9]+/
+    "#,
+    );
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+
+    // Every state lists its kernel items and actions in grammar notation.
+    let expected = r#"Grammar
+
+  <START> -> sum
+  sum -> sum "+" NUMBER
+  sum -> NUMBER
+
+State 0
+
+  <START> -> . sum
+
+  NUMBER  shift, and go to state 1
+  sum     go to state 2
+
+State 1
+
+  sum -> NUMBER .
+
+  "+"    reduce using sum -> NUMBER
+  <EOF>  reduce using sum -> NUMBER
+
+State 2
+
+  <START> -> sum .
+  sum -> sum . "+" NUMBER
+
+  "+"    shift, and go to state 3
+  <EOF>  accept
+
+State 3
+
+  sum -> sum "+" . NUMBER
+
+  NUMBER  shift, and go to state 4
+
+State 4
+
+  sum -> sum "+" NUMBER .
+
+  "+"    reduce using sum -> sum "+" NUMBER
+  <EOF>  reduce using sum -> sum "+" NUMBER
+"#;
+    assert_eq!(parse_table.report(), expected);
+}
+
+#[test]
+fn conflict_report() {
+    let result = generate(
+        r#"
+        statement = "if" ID "then" statement
+                  | "if" ID "then" statement "else" statement
+                  | ID
+        ID        = /[a-z]+/
+    "#,
+    );
+    let Err(report) = ParseTable::try_new(&result.grammar_rules, reduce_on, priority_of) else {
+        panic!("Grammar should have conflicts");
+    };
+
+    // The summary lists the conflicts with their items and counterexamples.
+    let expected = "\
+1 conflict: 1 shift/reduce, 0 reduce/reduce
+
+State 6: shift/reduce conflict on else
+  statement -> if ID then statement .
+  statement -> if ID then statement . else statement
+Reduce example: if ID then if ID then statement • else statement
+Reduce derivation: [statement -> if ID then [statement -> if ID then statement •] else statement]
+Shift example: if ID then if ID then statement • else statement
+Shift derivation: [statement -> if ID then [statement -> if ID then statement • else statement]]";
+    assert_eq!(report.to_string(), expected);
+
+    // The automaton shows the conflict in its state, next to the action kept in the table.
+    let expected = "\
+State 6
+
+  statement -> if ID then statement .
+  statement -> if ID then statement . else statement
+
+  else   shift, and go to state 7
+  <EOF>  reduce using statement -> if ID then statement
+
+  shift/reduce conflict on else with statement -> if ID then statement
+";
+    assert!(report.automaton().to_string().contains(expected));
+}
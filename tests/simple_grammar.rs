@@ -1,5 +1,6 @@
 mod tac_action;
 
+use rustcc::common::action::ValueStack;
 use rustcc::common::parse_table::ParseTable;
 use rustcc::compiler::lexer::Lexer;
 use rustcc::compiler::parser::Parser;
@@ -50,13 +51,13 @@ fn simple_grammar() {
     // Tokenize and parse the input.
     let tokens = lexer.tokenize(input);
     let processed = Processor::process(tokens);
-    let result = parser.parse(processed).unwrap();
+    let result = parser.parse(processed).unwrap().unwrap();
 
     // Build the lexer and parser based on the result.
     let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
     let mut parser = Parser::new(
         &parse_table.parse_table,
-        TacAction::new("output.txt".to_string()),
+        ValueStack::new(TacAction::new("output.txt".to_string())),
     );
 
     // Test the generated lexer and parser.
@@ -0,0 +1,104 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::{ParseTreeNode, Span};
+use rustcc::compiler::glr_parser::GLRParser;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Collect the symbols or lexemes of a parse tree with the text of their spans.
+fn collect_spans<'a>(node: &ParseTreeNode, input: &'a str, spans: &mut Vec<(String, &'a str)>) {
+    let span = node.span();
+    match node {
+        ParseTreeNode::Terminal { lexeme, .. } => {
+            spans.push((lexeme.clone(), &input[span.start..span.end]));
+        }
+        ParseTreeNode::NonTerminal {
+            symbol, children, ..
+        } => {
+            spans.push((symbol.to_string(), &input[span.start..span.end]));
+            for child in children {
+                collect_spans(child, input, spans);
+            }
+        }
+    }
+}
+
+#[test]
+fn spans() {
+    let grammar = r#"
+        program   = statements
+        statements = statements statement | EPSILON
+        statement = ID "=" value ";"
+        value     = NUMBER | EPSILON
+        ID        = /[a-z]+/
+        NUMBER    = /[0-9]+/
+    "#;
+    let result = generate(grammar);
+
+    // The generated tree points at the grammar source.
+    let rule = result
+        .parse_tree
+        .get_children()
+        .iter()
+        .find(|node| node.get_children().len() == 3)
+        .unwrap();
+    let span = rule.span();
+    // Identifier tokens of the EBNF grammar include their trailing whitespace.
+    assert_eq!(
+        &grammar[span.start..span.end],
+        "program   = statements\n        "
+    );
+    assert_eq!((span.line, span.column), (2, 9));
+
+    let input = "a = 1;\n  b = ;";
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+    );
+    let tree = parser.parse(lexer().tokenize(input)).unwrap();
+    let mut actual = Vec::new();
+    collect_spans(&tree, input, &mut actual);
+    assert_eq!(
+        actual,
+        [
+            ("program".to_string(), "a = 1;\n  b = ;"),
+            ("statements".to_string(), "a = 1;\n  b = ;"),
+            ("statements".to_string(), "a = 1;"),
+            ("statements".to_string(), ""),
+            ("statement".to_string(), "a = 1;"),
+            ("a".to_string(), "a"),
+            ("=".to_string(), "="),
+            ("value".to_string(), "1"),
+            ("1".to_string(), "1"),
+            (";".to_string(), ";"),
+            ("statement".to_string(), "b = ;"),
+            ("b".to_string(), "b"),
+            ("=".to_string(), "="),
+            ("value".to_string(), ""),
+            (";".to_string(), ";"),
+        ]
+    );
+
+    // Empty rules have the empty span before the lookahead.
+    let second = &tree.get_children()[0].get_children()[1];
+    assert_eq!(second.span(), Span::new(9, 14, 2, 3));
+    assert_eq!(second.get_children()[2].span(), Span::new(13, 13, 2, 7));
+
+    // The GLR parser derives the same spans.
+    let parse_table = ParseTable::glr(&result.grammar_rules, reduce_on, priority_of);
+    let forest = GLRParser::new(&parse_table)
+        .parse(lexer().tokenize(input))
+        .unwrap();
+    assert_eq!(forest.trees(), std::slice::from_ref(&tree));
+    let replayed = forest.replay(&mut DefaultAction::new(
+        result.grammar_rules.start_symbol.clone(),
+    ));
+    assert_eq!(replayed, tree);
+}
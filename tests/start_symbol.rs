@@ -0,0 +1,54 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::ParseTreeNode;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn start_symbol() {
+    // The start symbol has several alternatives and `unused` is never referenced.
+    let input = r#"
+        @start     = statement
+
+        statement  = assignment | expression
+        assignment = VARIABLE "=" expression
+        expression = expression "+" NUMBER | NUMBER
+        unused     = NUMBER NUMBER
+
+        NUMBER     = /[0-9]+/
+        VARIABLE   = /[a-z]+/
+    "#;
+
+    let result = generate(input);
+    assert_eq!(result.grammar_rules.start_symbol.0.as_ref(), "statement");
+
+    // Build the lexer and parser based on the result.
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+
+    // Every alternative of the start symbol is accepted, and the root is the start symbol.
+    for (test_input, alternative) in [("x = 1 + 2", "assignment"), ("1 + 2 + 3", "expression")] {
+        let lexer = Lexer::new(&result.token_rules);
+        let mut parser = Parser::new(
+            &parse_table.parse_table,
+            DefaultAction::new(result.grammar_rules.start_symbol.clone()),
+        );
+        let tree = parser.parse(lexer.tokenize(test_input)).unwrap();
+        let ParseTreeNode::NonTerminal {
+            symbol, children, ..
+        } = &tree
+        else {
+            panic!("Root should be a non terminal: {tree}");
+        };
+        assert_eq!(symbol.0.as_ref(), "statement");
+        assert_eq!(children.len(), 1);
+        assert!(matches!(
+            &children[0],
+            ParseTreeNode::NonTerminal { symbol, .. } if symbol.0.as_ref() == alternative
+        ));
+    }
+}
@@ -2,8 +2,9 @@ use std::fs::File;
 use std::io::{BufWriter, Write};
 
 use relex::Token;
-use rustcc::common::action::Action;
+use rustcc::common::action::ValueAction;
 use rustcc::common::grammar::Rhs;
+use rustcc::common::grammar_rules::Production;
 use rustcc::common::parse_tree::{ParseError, Span};
 use rustcc::common::symbol_table::{NonTerminal, Terminal};
 
@@ -21,7 +22,6 @@ pub enum SemanticValue {
 }
 
 pub struct TacAction {
-    semantic_stack: Vec<SemanticValue>,
     temp_counter: usize,
     instructions: Vec<String>,
     output_path: String,
@@ -30,7 +30,6 @@ pub struct TacAction {
 impl TacAction {
     pub fn new(output_path: String) -> Self {
         Self {
-            semantic_stack: Vec::new(),
             temp_counter: 0,
             instructions: Vec::new(),
             output_path,
@@ -65,154 +64,81 @@ impl TacAction {
     }
 }
 
-impl Action for TacAction {
+impl ValueAction for TacAction {
+    type Value = SemanticValue;
     type ParseResult = (); // We don't build a tree, we generate a file
     type ParseError = ParseError;
 
-    fn on_shift(&mut self, token: Token<Terminal>) {
-        // Push relevant tokens to the semantic stack
+    fn on_shift(&mut self, token: Token<Terminal>, _span: Span) -> Self::Value {
         let text = token.text.to_string();
         let kind = token.kind.0.as_ref();
 
         match kind {
-            "NUMBER" | "VARIABLE" => self.semantic_stack.push(SemanticValue::Addr(text)),
-            "PLUS" | "MINUS" | "MUL" | "DIV" | "POSITIVE" | "NEGATIVE" => {
-                self.semantic_stack.push(SemanticValue::Op(text))
-            }
-            _ => {
-                // For parens or other tokens, we might push them or ignore them.
-                // Pushing them helps keep stack aligned with grammar symbols.
-                self.semantic_stack.push(SemanticValue::Op(text));
-            }
+            "NUMBER" | "VARIABLE" => SemanticValue::Addr(text),
+            // Operators, and parens or other tokens the grammar keeps as children.
+            _ => SemanticValue::Op(text),
         }
     }
 
-    fn on_reduce(&mut self, non_terminal: &NonTerminal, rhs: &Rhs<Terminal, NonTerminal, ()>) {
+    fn on_reduce(
+        &mut self,
+        non_terminal: &NonTerminal,
+        _rhs: &Rhs<Terminal, NonTerminal, Production>,
+        children: &mut [Self::Value],
+        _span: Span,
+    ) -> Self::Value {
         let nt_name = non_terminal.0.as_ref();
-        let rhs_len = rhs.syms.len();
-
-        // Pop the RHS items from the stack
-        let mut args = Vec::with_capacity(rhs_len);
-        for _ in 0..rhs_len {
-            if let Some(val) = self.semantic_stack.pop() {
-                args.push(val);
-            }
-        }
-        args.reverse(); // Now args matches the order of symbols in RHS
 
-        match nt_name {
-            "program" => {
-                // program = program expression | EPSILON
-                // If it's EPSILON, args is empty.
-                // If it's program expression, we just keep going.
-            }
-            "expression" => {
-                // expression = term expression_tail
-                // args: [Addr(term), Partial(op, rhs) OR Empty]
-                if let [SemanticValue::Addr(lhs), tail] = &args[..] {
-                    match tail {
-                        SemanticValue::Empty => {
-                            // No tail, result is just the term
-                            self.semantic_stack.push(SemanticValue::Addr(lhs.clone()));
-                        }
-                        SemanticValue::Partial(op, rhs_val) => {
-                            // term OP rhs
-                            let result = self.new_temp();
-                            self.emit(op, lhs, rhs_val, &result);
-                            self.semantic_stack.push(SemanticValue::Addr(result));
-                        }
-                        _ => panic!("Invalid stack state for expression"),
-                    }
+        match (nt_name, &*children) {
+            // expression = term expression_tail, term = factor term_tail
+            ("expression" | "term", [SemanticValue::Addr(lhs), tail]) => match tail {
+                // No tail, result is just the term
+                SemanticValue::Empty => SemanticValue::Addr(lhs.clone()),
+                // term OP rhs
+                SemanticValue::Partial(op, rhs_val) => {
+                    let result = self.new_temp();
+                    self.emit(op, lhs, rhs_val, &result);
+                    SemanticValue::Addr(result)
                 }
-            }
-            "expression_tail" | "term_tail" => {
-                // tail = OP term tail | EPSILON
-                if args.is_empty() {
-                    // EPSILON
-                    self.semantic_stack.push(SemanticValue::Empty);
-                } else if let [SemanticValue::Op(op), SemanticValue::Addr(term), tail] = &args[..] {
-                    match tail {
-                        SemanticValue::Empty => {
-                            // End of chain: return (OP, term)
-                            self.semantic_stack
-                                .push(SemanticValue::Partial(op.clone(), term.clone()));
-                        }
-                        SemanticValue::Partial(next_op, next_val) => {
-                            // Chained: OP term (next_op next_val)
-                            // Because of tail recursion, we compute the inner part first (Right Associative)
-                            let result = self.new_temp();
-                            self.emit(next_op, term, next_val, &result);
-                            self.semantic_stack
-                                .push(SemanticValue::Partial(op.clone(), result));
-                        }
-                        _ => panic!("Invalid stack state for tail"),
-                    }
+                _ => panic!("Invalid stack state for {nt_name}"),
+            },
+            // tail = OP term tail | EPSILON
+            ("expression_tail" | "term_tail", []) => SemanticValue::Empty,
+            (
+                "expression_tail" | "term_tail",
+                [SemanticValue::Op(op), SemanticValue::Addr(term), tail],
+            ) => match tail {
+                // End of chain: return (OP, term)
+                SemanticValue::Empty => SemanticValue::Partial(op.clone(), term.clone()),
+                // Chained: OP term (next_op next_val)
+                // Because of tail recursion, we compute the inner part first (Right Associative)
+                SemanticValue::Partial(next_op, next_val) => {
+                    let result = self.new_temp();
+                    self.emit(next_op, term, next_val, &result);
+                    SemanticValue::Partial(op.clone(), result)
                 }
-            }
-            "term" => {
-                // term = factor term_tail
-                // Logic is identical to expression
-                if let [SemanticValue::Addr(lhs), tail] = &args[..] {
-                    match tail {
-                        SemanticValue::Empty => {
-                            self.semantic_stack.push(SemanticValue::Addr(lhs.clone()));
-                        }
-                        SemanticValue::Partial(op, rhs_val) => {
-                            let result = self.new_temp();
-                            self.emit(op, lhs, rhs_val, &result);
-                            self.semantic_stack.push(SemanticValue::Addr(result));
-                        }
-                        _ => panic!("Invalid stack state for term"),
-                    }
-                }
-            }
-            "factor" => {
-                // factor = sign factor_base | factor_base
-                if args.len() == 1 {
-                    // factor_base
-                    // Just pass it up
-                    self.semantic_stack.push(args[0].clone());
-                } else if let [SemanticValue::Op(sign), SemanticValue::Addr(base)] = &args[..] {
-                    // sign factor_base
-                    if sign == "-" {
-                        let result = self.new_temp();
-                        self.emit_unary("-", base, &result);
-                        self.semantic_stack.push(SemanticValue::Addr(result));
-                    } else {
-                        self.semantic_stack.push(SemanticValue::Addr(base.clone()));
-                    }
-                }
-            }
-            "factor_base" => {
-                // NUMBER | VARIABLE | group
-                if args.len() == 1 {
-                    // NUMBER or VARIABLE
-                    self.semantic_stack.push(args[0].clone());
+                _ => panic!("Invalid stack state for tail"),
+            },
+            // factor = sign factor_base
+            ("factor", [SemanticValue::Op(sign), SemanticValue::Addr(base)]) => {
+                if sign == "-" {
+                    let result = self.new_temp();
+                    self.emit_unary("-", base, &result);
+                    SemanticValue::Addr(result)
                 } else {
-                    // group -> "(" expression ")"
-                    // args: [Op("("), Addr(expr), Op(")")]
-                    if let [_, val, _] = &args[..] {
-                        self.semantic_stack.push(val.clone());
-                    }
-                }
-            }
-            "sign" => {
-                // POSITIVE | NEGATIVE
-                // Already pushed as Op in on_shift, just pass it up
-                self.semantic_stack.push(args[0].clone());
-            }
-            "group" => {
-                // group = "(" expression ")"
-                // Handled in factor_base usually, but if grammar has explicit group rule:
-                if let [_, val, _] = &args[..] {
-                    self.semantic_stack.push(val.clone());
+                    SemanticValue::Addr(base.clone())
                 }
             }
-            _ => {}
+            // group = "(" expression ")"
+            (_, [_, val, _]) => val.clone(),
+            // Pass single children, like NUMBER, VARIABLE, group or a sign, up.
+            (_, [val]) => val.clone(),
+            // program = program expression | EPSILON
+            _ => SemanticValue::Empty,
         }
     }
 
-    fn on_accept(&mut self) -> Self::ParseResult {
+    fn on_accept(&mut self, _value: Self::Value) -> Self::ParseResult {
         self.write_file();
         println!(
             "TAC generation complete. Output written to {}",
@@ -220,13 +146,19 @@ impl Action for TacAction {
         );
     }
 
-    fn on_error(&mut self, token: Token<Terminal>, span: Span) -> Self::ParseError {
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
         ParseError {
             message: format!(
                 "Syntax Error at {:?}: Unexpected token {:?}",
                 span, token.text
             ),
             span: Some(span),
+            expected: expected.to_vec(),
         }
     }
 }
@@ -0,0 +1,75 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::compiler::trace::TraceStep;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn trace() {
+    let result = generate(
+        r#"
+        statement = sum ";" | error ";"
+        sum       = sum "+" NUMBER | NUMBER
+        NUMBER    = /[0-9]+/
+    "#,
+    );
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let action = || DefaultAction::new(result.grammar_rules.start_symbol.clone());
+
+    // Every shift and reduce is written with the state stack and the lookahead.
+    let mut output = Vec::new();
+    Parser::new(&parse_table.parse_table, action())
+        .with_trace_writer(&mut output)
+        .parse(lexer().tokenize("1 + 2;"))
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert!(lines[0].starts_with(r#"[0] NUMBER "1" at 1:1: shift, go to state "#));
+    assert!(lines[1].contains(r#" "+" "+" at 1:3: reduce sum -> NUMBER, go to state "#));
+    assert!(lines[4].contains(r#" ";" ";" at 1:6: reduce sum -> sum "+" NUMBER, go to state "#));
+    assert!(lines[6].contains(r#"<EOF> "" at 1:7: reduce statement -> sum ";", go to state "#));
+    assert!(lines[7].ends_with(r#"<EOF> "" at 1:7: accept"#));
+
+    // Structured events follow the state stack, including error recovery.
+    let mut steps = Vec::new();
+    Parser::new(&parse_table.parse_table, action())
+        .with_trace(|event| {
+            let step = match event.step {
+                TraceStep::Shift(_) => "shift".to_string(),
+                TraceStep::Reduce { non_terminal, .. } => format!("reduce {non_terminal}"),
+                TraceStep::Accept => "accept".to_string(),
+                TraceStep::Error => "error".to_string(),
+                TraceStep::Pop => "pop".to_string(),
+                TraceStep::ShiftError(_) => "shift error".to_string(),
+                TraceStep::Discard => "discard".to_string(),
+            };
+            steps.push((
+                event.state_stack.len(),
+                event.lookahead.token.text.to_string(),
+                step,
+            ));
+        })
+        .parse_recovering(lexer().tokenize("1 + ;"));
+    assert_eq!(
+        steps,
+        [
+            (1, "1".to_string(), "shift".to_string()),
+            (2, "+".to_string(), "reduce sum".to_string()),
+            (2, "+".to_string(), "shift".to_string()),
+            (3, ";".to_string(), "error".to_string()),
+            (3, ";".to_string(), "pop".to_string()),
+            (2, ";".to_string(), "pop".to_string()),
+            (1, ";".to_string(), "shift error".to_string()),
+            (2, ";".to_string(), "shift".to_string()),
+            (3, "".to_string(), "reduce statement".to_string()),
+            (2, "".to_string(), "accept".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use rustcc::common::action::DefaultAction;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+#[test]
+fn hide_and_drop() {
+    // Arithmetic grammar hiding statements and dropping anonymous literals.
+    let input = r#"
+        @hide       = statement
+        @drop       = whitespace, strings
+
+        grammar    = { statement }
+        statement  = expression ";"
+        expression = term { (PLUS | MINUS) term }
+        term       = factor { (DIV | MUL) factor }
+        factor     = [ MINUS ] ( NUMBER | VARIABLE | group )
+        group      = "(" expression ")"
+
+        PLUS       = "+"
+        MINUS      = "-"
+        MUL        = "*"
+        DIV        = "/"
+
+        NUMBER     = /[0-9]+/
+        VARIABLE   = /[A-Za-z]/
+    "#;
+
+    let result = generate(input);
+
+    // Build the lexer and parser based on the result.
+    let lexer = Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let mut parser = Parser::new(
+        &parse_table.parse_table,
+        DefaultAction::with_tree_rules(result.grammar_rules.start_symbol, result.tree_rules),
+    );
+
+    // Helper non terminals and hidden statements are spliced, anonymous literals dropped.
+    let tokens = lexer.tokenize("1 + 2 * (a);");
+    let tree = parser.parse(tokens).unwrap();
+    let expected = r#"(grammar
+  (expression
+    (term
+      (factor
+        (NUMBER "1")
+      )
+    )
+    (PLUS "+")
+    (term
+      (factor
+        (NUMBER "2")
+      )
+      (MUL "*")
+      (factor
+        (group
+          (expression
+            (term
+              (factor
+                (VARIABLE "a")
+              )
+            )
+          )
+        )
+      )
+    )
+  )
+)
+"#;
+    assert_eq!(tree.to_string(), expected);
+}
+
+#[test]
+fn generator_tree() {
+    // The repetitions of the EBNF grammar are flattened by its own tree rules.
+    let result = generate("@drop = whitespace, strings\na = b { b } | EPSILON\nb = /x/\n");
+    let expected = r#"(Grammar
+  (Directive
+    (At "@")
+    (LeftIdentifier "drop")
+    (Equal "=")
+    (Value
+      (Identifier "whitespace")
+      (Comma ",")
+      (Identifier "strings")
+    )
+  )
+  (Rule
+    (LeftIdentifier "a")
+    (Equal "=")
+    (Alternative
+      (Factor
+        (Identifier "b")
+      )
+      (Factor
+        (Repetition
+          (LeftBrace "{")
+          (Alternative
+            (Factor
+              (Identifier "b")
+            )
+          )
+          (RightBrace "}")
+        )
+      )
+    )
+    (Pipe "|")
+    (Alternative
+      (Empty "EPSILON")
+    )
+  )
+  (Rule
+    (LeftIdentifier "b")
+    (Equal "=")
+    (Alternative
+      (Factor
+        (Regex "/x/")
+      )
+    )
+  )
+)
+"#;
+    assert_eq!(result.parse_tree.to_string(), expected);
+}
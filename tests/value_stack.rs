@@ -0,0 +1,100 @@
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use relex::Token;
+use rustcc::common::action::{ValueAction, ValueStack};
+use rustcc::common::grammar::Rhs;
+use rustcc::common::grammar_rules::Production;
+use rustcc::common::parse_table::ParseTable;
+use rustcc::common::parse_tree::{ParseError, Span};
+use rustcc::common::symbol_table::{NonTerminal, Terminal};
+use rustcc::compiler::lexer::Lexer;
+use rustcc::compiler::parser::Parser;
+use rustcc::generator::grammar_rules::{priority_of, reduce_on};
+
+use crate::common::generate;
+
+/// Action evaluating the sums and products of statements, with no value for erroneous ones.
+#[derive(Default)]
+struct Calculator {
+    results: Rc<RefCell<Vec<Option<i64>>>>,
+}
+
+impl ValueAction for Calculator {
+    type Value = Option<i64>;
+    type ParseResult = usize;
+    type ParseError = ParseError;
+
+    fn on_reduce(
+        &mut self,
+        non_terminal: &NonTerminal,
+        _rhs: &Rhs<Terminal, NonTerminal, Production>,
+        children: &mut [Self::Value],
+        _span: Span,
+    ) -> Self::Value {
+        match (non_terminal.0.as_ref(), &*children) {
+            ("statement", [value, _]) => {
+                self.results.borrow_mut().push(*value);
+                None
+            }
+            ("sum", [Some(lhs), _, Some(rhs)]) => Some(lhs + rhs),
+            ("product", [Some(lhs), _, Some(rhs)]) => Some(lhs * rhs),
+            ("sum" | "product", [value]) => *value,
+            _ => None,
+        }
+    }
+
+    fn on_shift(&mut self, token: Token<Terminal>, _span: Span) -> Self::Value {
+        token.text.parse().ok()
+    }
+
+    fn on_accept(&mut self, _value: Self::Value) -> Self::ParseResult {
+        self.results.borrow().len()
+    }
+
+    fn on_error(
+        &mut self,
+        token: Token<Terminal>,
+        span: Span,
+        expected: &[Terminal],
+    ) -> Self::ParseError {
+        ParseError::unexpected(&token.kind, expected, span)
+    }
+}
+
+#[test]
+fn value_stack() {
+    let result = generate(
+        r#"
+        program   = { statement }
+        statement = sum ";" | error ";"
+        sum       = sum "+" product | product
+        product   = product "*" NUMBER | NUMBER
+
+        NUMBER    = /[0-9]+/
+    "#,
+    );
+    let lexer = || Lexer::new(&result.token_rules);
+    let parse_table = ParseTable::new(&result.grammar_rules, reduce_on, priority_of);
+    let calculator = Calculator::default();
+    let results = Rc::clone(&calculator.results);
+    let mut parser = Parser::new(&parse_table.parse_table, ValueStack::new(calculator));
+
+    // The children of a rule are its values in order.
+    let count = parser.parse(lexer().tokenize("1 + 2 * 3; 4 * 5 + 6;"));
+    assert_eq!(count, Ok(2));
+    assert_eq!(results.take(), [Some(7), Some(26)]);
+
+    // The values of the symbols popped by error recovery are dropped.
+    let (count, errors) = parser.parse_recovering(lexer().tokenize("1 + 2 +; 3; 4 4; 5 * 6;"));
+    assert_eq!(count, Some(4));
+    assert_eq!(errors.len(), 2);
+    assert_eq!(results.take(), [None, Some(3), None, Some(30)]);
+
+    // A rejected input leaves an empty value stack for the next one.
+    assert!(parser.parse(lexer().tokenize("1 + 2")).is_err());
+    assert_eq!(parser.parse(lexer().tokenize("8 * 8;")), Ok(1));
+    assert_eq!(results.take(), [Some(64)]);
+}